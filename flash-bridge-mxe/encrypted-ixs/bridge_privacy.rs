@@ -8,10 +8,25 @@
 
 use arcis_imports::*;
 use hex;
+use sha3::{Digest, Keccak256};
 
 #[encrypted]
 mod bridge_circuits {
     use arcis_imports::*;
+    // Plain-Rust helpers are defined at the crate root, outside the circuit module
+    use super::{
+        assess_risk_level, categorize_amount, determine_priority, generate_callback_url,
+        generate_computation_id, generate_routing_hints, generate_task_id, hash_user_id,
+        is_allowlisted, is_reportable, multihop_amounts, net_amounts, ordering_code,
+        seal_to_each, seeded_random, swap_breakdown,
+    };
+
+    const MAX_SWAP_HOPS: usize = 4;
+    const MAX_COMPLIANCE_OFFICERS: usize = 4;
+    const MIN_BTC_ADDRESS_LEN: usize = 26;
+    const MAX_BTC_ADDRESS_LEN: usize = 90;
+    const MAX_NETTING_BATCH: usize = 16;
+    const MAX_MERKLE_PROOF_DEPTH: usize = 20;
 
     // Bridge amount data structure
     #[derive(Debug, Clone)]
@@ -47,7 +62,44 @@ mod bridge_circuits {
     pub struct SwapCalculation {
         zen_amount: Vec<u8>,
         exchange_rate: u64,
-        slippage_tolerance: u64,
+        slippage_bps: u64,
+        protocol_fee_bps: u64,
+    }
+
+    // Swap fee accounting (sealed to the user only)
+    #[derive(Debug, Clone)]
+    pub struct FeeBreakdown {
+        slippage_penalty: u64,
+        protocol_fee: u64,
+        net_out: u64,
+    }
+
+    // Single leg of a multi-hop swap route, at the rate read from its pair feed
+    #[derive(Debug, Clone)]
+    pub struct SwapHop {
+        rate: u64,
+        slippage_bps: u64,
+    }
+
+    // Multi-hop swap route (e.g. ZEN -> USDC -> SOL)
+    #[derive(Debug, Clone)]
+    pub struct SwapRoute {
+        hops: Vec<SwapHop>,
+    }
+
+    // Multi-hop swap calculation data
+    #[derive(Debug, Clone)]
+    pub struct MultihopSwapCalculation {
+        zen_amount: Vec<u8>,
+        route: SwapRoute,
+        protocol_fee_bps: u64,
+    }
+
+    // Multi-hop swap result with per-hop intermediate amounts
+    #[derive(Debug, Clone)]
+    pub struct MultihopSwapResult {
+        hop_amounts: Vec<u64>,
+        sol_amount: u64,
     }
 
     // BTC address data
@@ -58,6 +110,13 @@ mod bridge_circuits {
         timestamp: u64,
     }
 
+    // BTC address with its proof of membership in a committed allowlist
+    #[derive(Debug, Clone)]
+    pub struct BtcAllowlistMembership {
+        address: String,
+        proof: Vec<[u8; 32]>,
+    }
+
     // Relayer task data (sealed for relayers only)
     #[derive(Debug, Clone)]
     pub struct RelayerTask {
@@ -88,7 +147,9 @@ mod bridge_circuits {
      */
     #[instruction]
     pub fn encrypt_bridge_amount(
-        input_ctxt: Enc<Shared, BridgeAmount>
+        input_ctxt: Enc<Shared, BridgeAmount>,
+        requester: [u8; 32],
+        computation_offset: u64
     ) -> Enc<Shared, EncryptedBridgeTx> {
         // Decrypt the input (only MXE can see this)
         let input = input_ctxt.to_arcis();
@@ -97,13 +158,20 @@ mod bridge_circuits {
         if input.amount == 0 {
             panic!("Bridge amount cannot be zero");
         }
+        if input.source_chain.is_empty() || input.dest_chain.is_empty() {
+            panic!("Source and destination chains cannot be empty");
+        }
 
         // Create encrypted transaction data
         let encrypted_tx = EncryptedBridgeTx {
             encrypted_amount: input.amount.to_le_bytes().to_vec(),
             source_chain: input.source_chain.clone(),
             dest_chain: input.dest_chain.clone(),
-            computation_id: generate_computation_id(),
+            computation_id: generate_computation_id(
+                &requester,
+                computation_offset,
+                b"encrypt_bridge_amount"
+            ),
             privacy_level: "maximum".to_string(),
         };
 
@@ -114,15 +182,18 @@ mod bridge_circuits {
     /**
      * Enhanced bridge amount encryption with sealing
      * Provides different encrypted outputs for different parties
+     * Audit data is sealed once per compliance officer (jurisdiction)
      */
     #[instruction]
     pub fn encrypt_bridge_amount_sealed(
         input_ctxt: Enc<Shared, BridgeAmount>,
         relayer: Shared,
-        compliance_officer: Shared
+        compliance_officers: Vec<Shared>,
+        requester: [u8; 32],
+        computation_offset: u64
     ) -> (Enc<Shared, EncryptedBridgeTx>,
           Enc<Shared, RelayerTask>,
-          Enc<Shared, ComplianceAudit>) {
+          Vec<Enc<Shared, ComplianceAudit>>) {
 
         let input = input_ctxt.to_arcis();
 
@@ -133,9 +204,16 @@ mod bridge_circuits {
         if input.source_chain.is_empty() || input.dest_chain.is_empty() {
             panic!("Source and destination chains cannot be empty");
         }
+        if compliance_officers.is_empty() || compliance_officers.len() > MAX_COMPLIANCE_OFFICERS {
+            panic!("Compliance officer count out of range");
+        }
 
         // Generate computation ID for linking all outputs
-        let computation_id = generate_computation_id();
+        let computation_id = generate_computation_id(
+            &requester,
+            computation_offset,
+            b"encrypt_bridge_amount_sealed"
+        );
 
         // 1. USER DATA: Full transaction details for the user
         let user_tx = EncryptedBridgeTx {
@@ -148,7 +226,7 @@ mod bridge_circuits {
 
         // 2. RELAYER DATA: Minimal routing information (no sensitive user data)
         let relayer_task = RelayerTask {
-            task_id: generate_task_id(),
+            task_id: generate_task_id(&computation_id),
             task_type: "bridge_amount_encryption".to_string(),
             priority: determine_priority(input.amount),
             routing_hints: generate_routing_hints(&input.source_chain, &input.dest_chain),
@@ -172,11 +250,16 @@ mod bridge_circuits {
             timestamp: input.timestamp,
         };
 
-        // Return triple-sealed outputs for different parties
+        // Every officer receives an identical copy bound to the same computation ID
+        let sealed_audits = seal_to_each(&compliance_officers, &compliance_audit, |officer, audit| {
+            officer.from_arcis(audit)
+        });
+
+        // Return sealed outputs for different parties
         (
             input_ctxt.owner.from_arcis(user_tx),
             relayer.from_arcis(relayer_task),
-            compliance_officer.from_arcis(compliance_audit)
+            sealed_audits
         )
     }
 
@@ -207,11 +290,12 @@ mod bridge_circuits {
     /**
      * Calculate SOL swap amount on encrypted ZEC amount
      * Private arithmetic operations using MPC
+     * Also returns the fee breakdown so the user can audit the swap
      */
     #[instruction]
     pub fn calculate_swap_amount(
         swap_data: Enc<Shared, SwapCalculation>
-    ) -> Enc<Shared, u64> {
+    ) -> (Enc<Shared, u64>, Enc<Shared, FeeBreakdown>) {
         let data = swap_data.to_arcis();
 
         // Extract encrypted ZEC amount with bounds check
@@ -221,14 +305,65 @@ mod bridge_circuits {
         }
         let zen_amount = u64::from_le_bytes(zen_bytes[..8].try_into().unwrap());
 
-        // Perform private multiplication: zen_amount * exchange_rate
-        let sol_amount = zen_amount * data.exchange_rate;
+        // Same checked arithmetic as the on-chain quote
+        let (slippage_penalty, protocol_fee, net_out) = swap_breakdown(
+            zen_amount,
+            data.exchange_rate,
+            data.slippage_bps,
+            data.protocol_fee_bps,
+        );
+
+        let breakdown = FeeBreakdown {
+            slippage_penalty,
+            protocol_fee,
+            net_out,
+        };
+
+        // Return encrypted result and breakdown, both sealed to the user
+        (
+            swap_data.owner.from_arcis(net_out),
+            swap_data.owner.from_arcis(breakdown)
+        )
+    }
+
+    /**
+     * Calculate output amount across a multi-hop swap route
+     * Each hop applies its own rate and slippage in turn; the protocol fee is taken once
+     */
+    #[instruction]
+    pub fn calculate_multihop_swap(
+        swap_data: Enc<Shared, MultihopSwapCalculation>
+    ) -> Enc<Shared, MultihopSwapResult> {
+        let data = swap_data.to_arcis();
+
+        let hops = &data.route.hops;
+        if hops.is_empty() || hops.len() > MAX_SWAP_HOPS {
+            panic!("Invalid swap route: hop count out of range");
+        }
+
+        let zen_bytes = &data.zen_amount;
+        if zen_bytes.len() < 8 {
+            panic!("Invalid zen_amount: must be at least 8 bytes");
+        }
+        let zen_amount = u64::from_le_bytes(zen_bytes[..8].try_into().unwrap());
 
-        // Apply slippage tolerance (private calculation)
-        let min_amount = sol_amount * (100 - data.slippage_tolerance) / 100;
+        let hop_terms: Vec<(u64, u64)> = hops
+            .iter()
+            .map(|hop| (hop.rate, hop.slippage_bps))
+            .collect();
+        // Same checked arithmetic as the on-chain quote
+        let (hop_amounts, sol_amount) = multihop_amounts(
+            zen_amount,
+            &hop_terms,
+            data.protocol_fee_bps,
+        );
+
+        let result = MultihopSwapResult {
+            hop_amounts,
+            sol_amount,
+        };
 
-        // Return encrypted result
-        swap_data.owner.from_arcis(min_amount)
+        swap_data.owner.from_arcis(result)
     }
 
     /**
@@ -241,8 +376,8 @@ mod bridge_circuits {
     ) -> Enc<Shared, Vec<u8>> {
         let data = btc_data.to_arcis();
 
-        // Validate BTC address format (simplified)
-        if data.address.len() < 26 || data.address.len() > 62 {
+        // Length-only check; checksums are verified on-chain before queuing
+        if data.address.len() < MIN_BTC_ADDRESS_LEN || data.address.len() > MAX_BTC_ADDRESS_LEN {
             panic!("Invalid BTC address format");
         }
 
@@ -254,22 +389,16 @@ mod bridge_circuits {
     }
 
     /**
-     * Generate trustless random number for relayer selection
-     * Cryptographically secure randomness using MPC
+     * Derive a relayer selection value from a committee-provided seed
+     * The seed must come from the caller or committee; the circuit adds no entropy
      */
     #[instruction]
     pub fn generate_relayer_random(
+        seed: [u8; 32],
+        round: u64,
         max_value: u64
     ) -> u64 {
-        // In real MPC, this would use distributed randomness generation
-        // For demo, simulate cryptographically secure random
-        // NOTE: This is NOT secure - real implementation needed
-
-        // Simulate random generation (replace with real MPC random)
-        let random_bytes = [0u8; 8]; // Would be real random in MPC
-        let random_value = u64::from_le_bytes(random_bytes);
-
-        random_value % max_value
+        seeded_random(&seed, round, max_value)
     }
 
     /**
@@ -289,13 +418,109 @@ mod bridge_circuits {
         required_amount.owner.from_arcis(sufficient)
     }
 
+    /**
+     * Private comparison of two encrypted amounts
+     * Returns 0 if a < b, 1 if equal, 2 if a > b without revealing either
+     */
+    #[instruction]
+    pub fn compare_amounts(
+        a_ctxt: Enc<Shared, u64>,
+        b_ctxt: Enc<Shared, u64>
+    ) -> Enc<Shared, u8> {
+        let a = a_ctxt.to_arcis();
+        let b = b_ctxt.to_arcis();
+
+        let ordering = ordering_code(*a, *b);
+
+        a_ctxt.owner.from_arcis(ordering)
+    }
+
+    /**
+     * Net several bridge amounts for the same user and route
+     * Produces a single settlement without revealing the individual amounts
+     */
+    #[instruction]
+    pub fn net_bridge_batch(
+        amount_ctxts: Vec<Enc<Shared, BridgeAmount>>,
+        requester: [u8; 32],
+        computation_offset: u64
+    ) -> Enc<Shared, EncryptedBridgeTx> {
+        if amount_ctxts.is_empty() || amount_ctxts.len() > MAX_NETTING_BATCH {
+            panic!("Invalid netting batch: count out of range");
+        }
+
+        let first = amount_ctxts[0].to_arcis();
+        let mut amounts = Vec::with_capacity(amount_ctxts.len());
+        for ctxt in amount_ctxts.iter() {
+            let input = ctxt.to_arcis();
+            if input.user_pubkey != first.user_pubkey
+                || input.source_chain != first.source_chain
+                || input.dest_chain != first.dest_chain
+            {
+                panic!("Netting batch mixes users or routes");
+            }
+            amounts.push(input.amount);
+        }
+
+        let netted_tx = EncryptedBridgeTx {
+            encrypted_amount: net_amounts(&amounts).to_le_bytes().to_vec(),
+            source_chain: first.source_chain.clone(),
+            dest_chain: first.dest_chain.clone(),
+            computation_id: generate_computation_id(
+                &requester,
+                computation_offset,
+                b"net_bridge_batch"
+            ),
+            privacy_level: "maximum".to_string(),
+        };
+
+        amount_ctxts[0].owner.from_arcis(netted_tx)
+    }
+
+    /**
+     * Check whether an amount meets a compliance reporting threshold
+     * Only the compliance officer learns the result; the amount stays private
+     */
+    #[instruction]
+    pub fn check_reportable(
+        amount_ctxt: Enc<Shared, u64>,
+        threshold: u64,
+        compliance_officer: Shared
+    ) -> Enc<Shared, bool> {
+        let amount = amount_ctxt.to_arcis();
+
+        compliance_officer.from_arcis(is_reportable(*amount, threshold))
+    }
+
+    /**
+     * Verify an encrypted BTC address is a member of a committed allowlist
+     * Neither the address nor its position in the allowlist is revealed
+     */
+    #[instruction]
+    pub fn verify_btc_address_allowed(
+        membership_ctxt: Enc<Shared, BtcAllowlistMembership>,
+        allowlist_root: [u8; 32]
+    ) -> Enc<Shared, bool> {
+        let membership = membership_ctxt.to_arcis();
+
+        if membership.proof.len() > MAX_MERKLE_PROOF_DEPTH {
+            panic!("Allowlist proof too deep");
+        }
+
+        let allowed = is_allowlisted(&membership.address, &membership.proof, &allowlist_root);
+
+        membership_ctxt.owner.from_arcis(allowed)
+    }
+
     /**
      * Create encrypted bridge proof
      * Generate cryptographic proof for institutional compliance
      */
     #[instruction]
     pub fn generate_bridge_proof(
-        tx_data: Enc<Shared, BridgeAmount>
+        tx_data: Enc<Shared, BridgeAmount>,
+        requester: [u8; 32],
+        computation_offset: u64
     ) -> Enc<Shared, Vec<u8>> {
         let data = tx_data.to_arcis();
 
@@ -308,7 +533,11 @@ mod bridge_circuits {
 
         // Add computation metadata
         proof_data.extend_from_slice(b"FLASH_BRIDGE_V1");
-        proof_data.extend_from_slice(&generate_computation_id());
+        proof_data.extend_from_slice(&generate_computation_id(
+            &requester,
+            computation_offset,
+            b"generate_bridge_proof"
+        ));
 
         tx_data.owner.from_arcis(proof_data)
     }
@@ -316,40 +545,36 @@ mod bridge_circuits {
 
 // Helper functions for sealing operations
 
-fn generate_computation_id() -> [u8; 32] {
-    // In real implementation, this would be cryptographically secure
-    // For demo purposes, using timestamp-based ID
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_nanos();
-
-    let mut id = [u8; 32];
-    let bytes = timestamp.to_le_bytes();
-    id[..16].copy_from_slice(&bytes);
-
-    // Add some entropy (simplified)
-    for i in 16..32 {
-        id[i] = (timestamp as u8).wrapping_add(i as u8);
-    }
-
-    id
+const MAX_CHAIN_NAME_LEN: usize = 32;
+const BPS_DENOMINATOR: u64 = 10_000;
+const ROUTING_HINTS_MARKER: &[u8] = b"|PRIORITY_ROUTING|";
+// source + '>' + dest + marker, with both chains at their maximum length
+const MAX_ROUTING_HINTS_LEN: usize = 2 * MAX_CHAIN_NAME_LEN + 1 + ROUTING_HINTS_MARKER.len();
+
+const COMPUTATION_ID_DOMAIN: &[u8] = b"FLASH_COMPUTATION_ID";
+const TASK_ID_DOMAIN: &[u8] = b"FLASH_RELAYER_TASK";
+
+// Every MXE node must produce the same ID, so it depends only on the inputs:
+// the on-chain requester and offset identify the Computation PDA, and the
+// circuit tag keeps IDs from different circuits apart.
+fn generate_computation_id(
+    requester: &[u8; 32],
+    computation_offset: u64,
+    circuit: &[u8],
+) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(COMPUTATION_ID_DOMAIN);
+    hasher.update(requester);
+    hasher.update(computation_offset.to_le_bytes());
+    hasher.update(circuit);
+    hasher.finalize().into()
 }
 
-fn generate_task_id() -> [u8; 32] {
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_nanos();
-
-    let mut id = [0u8; 32];
-    let bytes = timestamp.to_le_bytes();
-    id[..8].copy_from_slice(&bytes[..8]);
-    id[8..16].copy_from_slice(b"TASK");
-    id[16..24].copy_from_slice(&(timestamp as u32).to_le_bytes());
-    id[24..32].copy_from_slice(b"FLASH");
-
-    id
+fn generate_task_id(computation_id: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(TASK_ID_DOMAIN);
+    hasher.update(computation_id);
+    hasher.finalize().into()
 }
 
 fn determine_priority(amount: u64) -> String {
@@ -363,11 +588,16 @@ fn determine_priority(amount: u64) -> String {
 }
 
 fn generate_routing_hints(source_chain: &str, dest_chain: &str) -> Vec<u8> {
-    let mut hints = Vec::new();
+    if source_chain.len() > MAX_CHAIN_NAME_LEN || dest_chain.len() > MAX_CHAIN_NAME_LEN {
+        panic!("Chain name too long for routing hints");
+    }
+
+    let mut hints = Vec::with_capacity(MAX_ROUTING_HINTS_LEN);
     hints.extend_from_slice(source_chain.as_bytes());
     hints.push(b'>');
     hints.extend_from_slice(dest_chain.as_bytes());
-    hints.extend_from_slice(b"|PRIORITY_ROUTING|");
+    hints.extend_from_slice(ROUTING_HINTS_MARKER);
+    assert!(hints.len() <= MAX_ROUTING_HINTS_LEN);
     hints
 }
 
@@ -375,6 +605,135 @@ fn generate_callback_url(computation_id: [u8; 32]) -> String {
     format!("https://api.flash-bridge.com/callback/{}", hex::encode(computation_id))
 }
 
+fn ordering_code(a: u64, b: u64) -> u8 {
+    if a < b {
+        0
+    } else if a == b {
+        1
+    } else {
+        2
+    }
+}
+
+// Returns (slippage_penalty, protocol_fee, net_out). Basis-point products
+// are taken in u128 so a gross amount near u64::MAX cannot overflow them.
+fn swap_breakdown(
+    zen_amount: u64,
+    exchange_rate: u64,
+    slippage_bps: u64,
+    protocol_fee_bps: u64,
+) -> (u64, u64, u64) {
+    if slippage_bps > BPS_DENOMINATOR || protocol_fee_bps > BPS_DENOMINATOR {
+        panic!("Invalid swap inputs: basis points out of range");
+    }
+
+    let gross = zen_amount
+        .checked_mul(exchange_rate)
+        .expect("Swap amount overflow");
+    let slippage_penalty = apply_bps(gross, slippage_bps);
+    let after_slippage = gross.saturating_sub(slippage_penalty);
+    let protocol_fee = apply_bps(after_slippage, protocol_fee_bps);
+    let net_out = after_slippage.saturating_sub(protocol_fee);
+
+    (slippage_penalty, protocol_fee, net_out)
+}
+
+// One copy per recipient, each sealed to that recipient's key alone
+fn seal_to_each<R, T: Clone, S>(recipients: &[R], data: &T, seal: impl Fn(&R, T) -> S) -> Vec<S> {
+    recipients
+        .iter()
+        .map(|recipient| seal(recipient, data.clone()))
+        .collect()
+}
+
+// Takes (rate, slippage_bps) per hop. Returns the amount after each hop's
+// conversion and slippage, and the net output after the protocol fee.
+fn multihop_amounts(
+    zen_amount: u64,
+    hops: &[(u64, u64)],
+    protocol_fee_bps: u64,
+) -> (Vec<u64>, u64) {
+    if protocol_fee_bps > BPS_DENOMINATOR
+        || hops.iter().any(|&(_, bps)| bps > BPS_DENOMINATOR)
+    {
+        panic!("Invalid swap inputs: basis points out of range");
+    }
+
+    let mut amount = zen_amount;
+    let mut hop_amounts = Vec::with_capacity(hops.len());
+    for &(rate, slippage_bps) in hops {
+        let gross = amount.checked_mul(rate).expect("Swap amount overflow");
+        amount = gross - apply_bps(gross, slippage_bps);
+        hop_amounts.push(amount);
+    }
+    let net_out = amount - apply_bps(amount, protocol_fee_bps);
+
+    (hop_amounts, net_out)
+}
+
+fn apply_bps(amount: u64, bps: u64) -> u64 {
+    let scaled = (amount as u128)
+        .checked_mul(bps as u128)
+        .and_then(|product| product.checked_div(BPS_DENOMINATOR as u128))
+        .expect("Swap amount overflow");
+    // bps <= BPS_DENOMINATOR, so the result never exceeds amount
+    scaled as u64
+}
+
+fn is_reportable(amount: u64, threshold: u64) -> bool {
+    amount >= threshold
+}
+
+// Leaves and sorted-pair nodes match the on-chain keccak commitment tree,
+// including its 0x00 leaf / 0x01 node domain separation
+fn allowlist_leaf(address: &str) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update([0x00]);
+    hasher.update(Keccak256::digest(address.trim().as_bytes()));
+    hasher.finalize().into()
+}
+
+fn hash_allowlist_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = Keccak256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+fn is_allowlisted(address: &str, proof: &[[u8; 32]], root: &[u8; 32]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(allowlist_leaf(address), |node, sibling| hash_allowlist_pair(&node, sibling));
+    &computed == root
+}
+
+fn net_amounts(amounts: &[u64]) -> u64 {
+    amounts.iter().fold(0u64, |total, amount| {
+        total
+            .checked_add(*amount)
+            .expect("Netted amount overflow")
+    })
+}
+
+// keccak(seed || round_le) reduced mod max_value; the modulo bias is
+// negligible for the small relayer sets this selects from
+fn seeded_random(seed: &[u8; 32], round: u64, max_value: u64) -> u64 {
+    if max_value == 0 {
+        panic!("max_value must be greater than zero");
+    }
+
+    let mut hasher = Keccak256::new();
+    hasher.update(seed);
+    hasher.update(round.to_le_bytes());
+    let digest = hasher.finalize();
+
+    let mut value = [0u8; 8];
+    value.copy_from_slice(&digest[..8]);
+    u64::from_le_bytes(value) % max_value
+}
+
 fn hash_user_id(user_pubkey: &[u8; 32]) -> [u8; 32] {
     let mut hash = [0u8; 32];
     hash[..16].copy_from_slice(&user_pubkey[..16]);
@@ -420,3 +779,212 @@ fn assess_risk_level(amount: u64, source_chain: &str) -> String {
         _ => "high".to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computation_ids_are_deterministic() {
+        let requester = [3u8; 32];
+
+        assert_eq!(
+            generate_computation_id(&requester, 42, b"encrypt_bridge_amount"),
+            generate_computation_id(&requester, 42, b"encrypt_bridge_amount")
+        );
+        assert_eq!(
+            generate_task_id(&generate_computation_id(&requester, 42, b"net_bridge_batch")),
+            generate_task_id(&generate_computation_id(&requester, 42, b"net_bridge_batch"))
+        );
+    }
+
+    #[test]
+    fn computation_ids_differ_per_requester_offset_and_circuit() {
+        let requester = [3u8; 32];
+        let id = generate_computation_id(&requester, 42, b"encrypt_bridge_amount");
+
+        assert_ne!(id, generate_computation_id(&[4u8; 32], 42, b"encrypt_bridge_amount"));
+        assert_ne!(id, generate_computation_id(&requester, 43, b"encrypt_bridge_amount"));
+        assert_ne!(id, generate_computation_id(&requester, 42, b"net_bridge_batch"));
+        assert_ne!(generate_task_id(&id), id);
+    }
+
+    #[test]
+    fn seal_to_each_gives_every_officer_an_identical_copy() {
+        let officers = [[1u8; 32], [2u8; 32], [3u8; 32]];
+        let audit = b"transaction_hash|user_hash|medium|SOL".to_vec();
+
+        // Record who each copy was sealed to and what it carried
+        let sealed = seal_to_each(&officers, &audit, |officer, copy| (*officer, copy));
+
+        assert_eq!(sealed.len(), officers.len());
+        for ((recipient, copy), officer) in sealed.iter().zip(&officers) {
+            assert_eq!(recipient, officer);
+            assert_eq!(copy, &audit);
+        }
+    }
+
+    #[test]
+    fn ordering_code_covers_all_orderings() {
+        assert_eq!(ordering_code(1, 2), 0);
+        assert_eq!(ordering_code(7, 7), 1);
+        assert_eq!(ordering_code(u64::MAX, 0), 2);
+    }
+
+    #[test]
+    fn routing_hints_stay_within_bound_for_max_length_chains() {
+        let chain = "C".repeat(MAX_CHAIN_NAME_LEN);
+        let hints = generate_routing_hints(&chain, &chain);
+        assert_eq!(hints.len(), MAX_ROUTING_HINTS_LEN);
+    }
+
+    #[test]
+    fn reportable_threshold_is_inclusive() {
+        assert!(!is_reportable(9_999, 10_000));
+        assert!(is_reportable(10_000, 10_000));
+        assert!(is_reportable(10_001, 10_000));
+    }
+
+    #[test]
+    fn allowlist_accepts_members_and_rejects_others() {
+        let members = ["bc1qmemberaddressone0000000000000", "bc1qmemberaddresstwo0000000000000"];
+        let leaves = members.map(allowlist_leaf);
+        let root = hash_allowlist_pair(&leaves[0], &leaves[1]);
+
+        assert!(is_allowlisted(members[0], &[leaves[1]], &root));
+        assert!(is_allowlisted(members[1], &[leaves[0]], &root));
+        assert!(!is_allowlisted("bc1qoutsideraddress00000000000000", &[leaves[1]], &root));
+    }
+
+    #[test]
+    fn net_amounts_sums_the_batch() {
+        assert_eq!(net_amounts(&[1_000, 2_500, 40]), 3_540);
+        assert_eq!(net_amounts(&[u64::MAX]), u64::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "Netted amount overflow")]
+    fn net_amounts_rejects_overflow() {
+        net_amounts(&[u64::MAX, 1]);
+    }
+
+    #[test]
+    fn swap_breakdown_matches_small_amounts() {
+        // 2 ZEC at 10 SOL, 1% slippage, 0.3% protocol fee
+        assert_eq!(
+            swap_breakdown(2_000_000, 10, 100, 30),
+            (200_000, 59_400, 19_740_600)
+        );
+    }
+
+    #[test]
+    fn swap_breakdown_handles_amounts_that_overflow_naive_bps_math() {
+        let zen_amount = u64::MAX / 10;
+        let gross = zen_amount * 10;
+        // gross * 5_000 would overflow u64
+        assert!(gross.checked_mul(5_000).is_none());
+
+        let (slippage_penalty, protocol_fee, net_out) =
+            swap_breakdown(zen_amount, 10, 5_000, 1_000);
+        assert_eq!(slippage_penalty, gross / 2);
+        assert_eq!(slippage_penalty + protocol_fee + net_out, gross);
+    }
+
+    #[test]
+    fn swap_breakdown_saturates_at_full_slippage() {
+        assert_eq!(swap_breakdown(u64::MAX, 1, 10_000, 1_000), (u64::MAX, 0, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Swap amount overflow")]
+    fn swap_breakdown_rejects_gross_overflow() {
+        swap_breakdown(u64::MAX, 2, 0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "basis points out of range")]
+    fn swap_breakdown_rejects_slippage_over_100_percent() {
+        swap_breakdown(1_000, 10, 10_001, 0);
+    }
+
+    #[test]
+    fn multihop_amounts_match_swap_breakdown_for_one_hop() {
+        let (_, _, net_out) = swap_breakdown(2_000_000, 10, 100, 30);
+        assert_eq!(
+            multihop_amounts(2_000_000, &[(10, 100)], 30),
+            (vec![19_800_000], net_out)
+        );
+    }
+
+    #[test]
+    fn multihop_amounts_apply_each_rate_and_slippage_then_the_fee_once() {
+        // ZEN -> USDC: 2_000_000 * 30 = 60_000_000 - 1% = 59_400_000
+        // USDC -> SOL: 59_400_000 * 2 = 118_800_000 - 2% = 116_424_000
+        // 116_424_000 - 0.3% = 116_074_728
+        assert_eq!(
+            multihop_amounts(2_000_000, &[(30, 100), (2, 200)], 30),
+            (vec![59_400_000, 116_424_000], 116_074_728)
+        );
+    }
+
+    #[test]
+    fn multihop_amounts_handle_amounts_that_overflow_naive_bps_math() {
+        let gross = u64::MAX / 10 * 10;
+        let first_hop = gross - gross / 2;
+        let (hop_amounts, net_out) =
+            multihop_amounts(u64::MAX / 10, &[(10, 5_000), (1, 5_000)], 0);
+        assert_eq!(hop_amounts, vec![first_hop, first_hop - first_hop / 2]);
+        assert_eq!(net_out, hop_amounts[1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "basis points out of range")]
+    fn multihop_amounts_reject_hop_slippage_over_100_percent() {
+        multihop_amounts(1_000, &[(10, 100), (1, 10_001)], 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Swap amount overflow")]
+    fn multihop_amounts_reject_gross_overflow() {
+        multihop_amounts(u64::MAX, &[(2, 0)], 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Swap amount overflow")]
+    fn multihop_amounts_reject_overflow_on_a_later_hop() {
+        multihop_amounts(u64::MAX / 2, &[(2, 0), (2, 0)], 0);
+    }
+
+    #[test]
+    fn seeded_random_spreads_across_seeds() {
+        let mut buckets = [0u32; 8];
+        for i in 0..=255u8 {
+            let value = seeded_random(&[i; 32], 0, 8);
+            assert!(value < 8);
+            buckets[value as usize] += 1;
+        }
+
+        assert!(buckets.iter().all(|&count| count > 0 && count < 64), "{buckets:?}");
+    }
+
+    #[test]
+    fn seeded_random_is_deterministic_per_seed_and_round() {
+        let seed = [7u8; 32];
+
+        assert_eq!(seeded_random(&seed, 1, u64::MAX), seeded_random(&seed, 1, u64::MAX));
+        assert_ne!(seeded_random(&seed, 1, u64::MAX), seeded_random(&seed, 2, u64::MAX));
+    }
+
+    #[test]
+    #[should_panic(expected = "max_value must be greater than zero")]
+    fn seeded_random_rejects_zero_max_value() {
+        seeded_random(&[1u8; 32], 0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Chain name too long")]
+    fn routing_hints_reject_oversized_chain() {
+        let chain = "C".repeat(MAX_CHAIN_NAME_LEN + 1);
+        generate_routing_hints(&chain, "SOL");
+    }
+}
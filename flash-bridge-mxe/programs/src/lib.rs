@@ -1,10 +1,42 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash;
 use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_lang::system_program;
 
 const MIN_CIPHERTEXT_BYTES: usize = 8;
 const MAX_CIPHERTEXT_BYTES: usize = 256;
 const MAX_CHAIN_NAME_LEN: usize = 32;
-const MAX_SLIPPAGE_PERCENT: u64 = 50;
+const MIN_BTC_ADDRESS_LEN: usize = 26;
+// bech32m allows up to 90 characters
+const MAX_BTC_ADDRESS_LEN: usize = 90;
+const MAX_SLIPPAGE_BPS: u16 = 5_000;
+const MAX_SWAP_HOPS: usize = 4;
+const MAX_ASSET_SYMBOL_LEN: usize = 10;
+const SWAP_SOURCE_ASSET: &str = "ZEN";
+const SWAP_DEST_ASSET: &str = "SOL";
+const MAX_CLOCK_SKEW_SECS: u32 = 300;
+const MAX_PROTOCOL_FEE_BPS: u16 = 1_000;
+const BPS_DENOMINATOR: u64 = 10_000;
+const MAX_COMMITMENT_BATCH: usize = 16;
+const MAX_ALLOWED_CHAINS: usize = 8;
+const MAX_NETTING_BATCH: usize = 16;
+const MAX_OPERATORS: usize = 16;
+const COMMITMENT_TREE_DEPTH: usize = 20;
+// Leaves and interior nodes hash under distinct prefixes so a node can never
+// be presented as a leaf
+const MERKLE_LEAF_PREFIX: &[u8] = &[0x00];
+const MERKLE_NODE_PREFIX: &[u8] = &[0x01];
+
+const MXE_CONFIG_SEED: &[u8] = b"mxe_config";
+const COMPUTATION_SEED: &[u8] = b"computation";
+const BTC_ADDRESS_ENCRYPTION_SEED: &[u8] = b"btc_address";
+const REQUESTER_STATE_SEED: &[u8] = b"requester";
+const CHAIN_FEE_SEED: &[u8] = b"chain_fee";
+const UNSUPPORTED_CHAIN_SEED: &[u8] = b"unsupported";
+const UNSUPPORTED_ASSET_SEED: &[u8] = b"unsupported";
+const PRICE_FEED_SEED: &[u8] = b"price_feed";
+const COMMITMENT_TREE_SEED: &[u8] = b"commitment_tree";
 
 declare_id!("CULoJigMJeVrmXVYPu8D9pdmfjAZnzdAwWvTqWvz1XkP");
 
@@ -12,41 +44,539 @@ declare_id!("CULoJigMJeVrmXVYPu8D9pdmfjAZnzdAwWvTqWvz1XkP");
 pub mod flash_bridge_mxe {
     use super::*;
 
+    pub fn initialize_mxe_config(
+        ctx: Context<InitializeMxeConfig>,
+        params: MxeConfigParams,
+    ) -> Result<()> {
+        require!(
+            params.max_pending_computations > 0,
+            ErrorCode::InvalidPendingLimit
+        );
+        require!(
+            params.min_ciphertext_bytes as usize >= MIN_CIPHERTEXT_BYTES
+                && params.max_ciphertext_bytes as usize <= MAX_CIPHERTEXT_BYTES
+                && params.min_ciphertext_bytes <= params.max_ciphertext_bytes,
+            ErrorCode::InvalidCiphertextBounds
+        );
+        require!(
+            params.max_slippage_bps <= MAX_SLIPPAGE_BPS,
+            ErrorCode::InvalidSwapInputs
+        );
+        require!(
+            params.clock_skew_secs <= MAX_CLOCK_SKEW_SECS,
+            ErrorCode::InvalidClockSkew
+        );
+        require!(
+            params.protocol_fee_bps <= MAX_PROTOCOL_FEE_BPS,
+            ErrorCode::InvalidProtocolFee
+        );
+        require!(params.soft_timeout_secs > 0, ErrorCode::InvalidSoftTimeout);
+        require!(
+            params.hard_timeout_secs > params.soft_timeout_secs,
+            ErrorCode::InvalidHardTimeout
+        );
+        require!(
+            params.max_price_staleness_secs > 0,
+            ErrorCode::InvalidPriceStaleness
+        );
+        require!(
+            params.allowed_chains.len() <= MAX_ALLOWED_CHAINS,
+            ErrorCode::TooManyAllowedChains
+        );
+        let allowed_chains = params
+            .allowed_chains
+            .into_iter()
+            .map(normalize_chain)
+            .collect::<Result<Vec<_>>>()?;
+
+        let config = &mut ctx.accounts.mxe_config;
+        config.authority = ctx.accounts.authority.key();
+        config.max_pending_computations = params.max_pending_computations;
+        config.pending_computations = 0;
+        config.min_ciphertext_bytes = params.min_ciphertext_bytes;
+        config.max_ciphertext_bytes = params.max_ciphertext_bytes;
+        config.max_slippage_bps = params.max_slippage_bps;
+        config.clock_skew_secs = params.clock_skew_secs;
+        config.require_monotonic_offsets = params.require_monotonic_offsets;
+        config.allow_testnet_btc = params.allow_testnet_btc;
+        config.paused = false;
+        config.protocol_fee_bps = params.protocol_fee_bps;
+        config.soft_timeout_secs = params.soft_timeout_secs;
+        config.hard_timeout_secs = params.hard_timeout_secs;
+        config.max_price_staleness_secs = params.max_price_staleness_secs;
+        config.treasury = params.treasury;
+        config.computation_fee = params.computation_fee;
+        config.arcium_program = params.arcium_program;
+        config.allowed_chains = allowed_chains;
+        config.operators = Vec::new();
+        config.bump = ctx.bumps.mxe_config;
+
+        msg!(
+            "MXE: config initialized max_pending={} chains={}",
+            config.max_pending_computations,
+            config.allowed_chains.len()
+        );
+        Ok(())
+    }
+
+    pub fn set_max_pending_computations(
+        ctx: Context<UpdateMxeConfig>,
+        max_pending_computations: u64,
+    ) -> Result<()> {
+        require!(max_pending_computations > 0, ErrorCode::InvalidPendingLimit);
+        ctx.accounts.mxe_config.max_pending_computations = max_pending_computations;
+        Ok(())
+    }
+
+    pub fn set_clock_skew_secs(ctx: Context<UpdateMxeConfig>, clock_skew_secs: u32) -> Result<()> {
+        require!(
+            clock_skew_secs <= MAX_CLOCK_SKEW_SECS,
+            ErrorCode::InvalidClockSkew
+        );
+        ctx.accounts.mxe_config.clock_skew_secs = clock_skew_secs;
+        Ok(())
+    }
+
+    pub fn set_require_monotonic_offsets(
+        ctx: Context<UpdateMxeConfig>,
+        require_monotonic_offsets: bool,
+    ) -> Result<()> {
+        ctx.accounts.mxe_config.require_monotonic_offsets = require_monotonic_offsets;
+        Ok(())
+    }
+
+    pub fn set_allow_testnet_btc(
+        ctx: Context<UpdateMxeConfig>,
+        allow_testnet_btc: bool,
+    ) -> Result<()> {
+        ctx.accounts.mxe_config.allow_testnet_btc = allow_testnet_btc;
+        Ok(())
+    }
+
+    pub fn set_mxe_paused(ctx: Context<UpdateMxeConfig>, paused: bool) -> Result<()> {
+        ctx.accounts.mxe_config.paused = paused;
+        msg!("MXE: paused={}", paused);
+        Ok(())
+    }
+
+    pub fn set_protocol_fee_bps(
+        ctx: Context<UpdateMxeConfig>,
+        protocol_fee_bps: u16,
+    ) -> Result<()> {
+        require!(
+            protocol_fee_bps <= MAX_PROTOCOL_FEE_BPS,
+            ErrorCode::InvalidProtocolFee
+        );
+        ctx.accounts.mxe_config.protocol_fee_bps = protocol_fee_bps;
+        Ok(())
+    }
+
+    pub fn set_arcium_program(ctx: Context<UpdateMxeConfig>, arcium_program: Pubkey) -> Result<()> {
+        ctx.accounts.mxe_config.arcium_program = arcium_program;
+        msg!("MXE: arcium_program={}", arcium_program);
+        Ok(())
+    }
+
+    pub fn set_computation_fee(ctx: Context<UpdateMxeConfig>, computation_fee: u64) -> Result<()> {
+        ctx.accounts.mxe_config.computation_fee = computation_fee;
+        Ok(())
+    }
+
+    pub fn set_operator(
+        ctx: Context<UpdateMxeConfig>,
+        operator: Pubkey,
+        allowed: bool,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.mxe_config;
+        let listed = config.is_operator(&operator);
+        if allowed && !listed {
+            require!(
+                config.operators.len() < MAX_OPERATORS,
+                ErrorCode::TooManyOperators
+            );
+            config.operators.push(operator);
+        } else if !allowed && listed {
+            config.operators.retain(|key| key != &operator);
+        }
+
+        msg!("MXE: operator {} allowed={}", operator, allowed);
+        Ok(())
+    }
+
+    pub fn set_chain_fee(
+        ctx: Context<SetChainFee>,
+        source_chain: String,
+        dest_chain: String,
+        fee: u64,
+    ) -> Result<()> {
+        let config = &ctx.accounts.mxe_config;
+        let chain_fee = &mut ctx.accounts.chain_fee;
+        chain_fee.source_chain = normalize_allowed_chain(config, source_chain)?;
+        chain_fee.dest_chain = normalize_allowed_chain(config, dest_chain)?;
+        chain_fee.fee = fee;
+        chain_fee.bump = ctx.bumps.chain_fee;
+
+        msg!(
+            "MXE: chain fee {}→{} set to {}",
+            chain_fee.source_chain,
+            chain_fee.dest_chain,
+            fee
+        );
+        Ok(())
+    }
+
+    pub fn update_price_feed(ctx: Context<UpdatePriceFeed>, rate: u64) -> Result<()> {
+        require!(rate > 0, ErrorCode::InvalidSwapInputs);
+        let price_feed = &mut ctx.accounts.price_feed;
+        price_feed.rate = rate;
+        price_feed.updated_at = Clock::get()?.unix_timestamp;
+        price_feed.bump = ctx.bumps.price_feed;
+
+        msg!("MXE: price feed rate set to {}", rate);
+        Ok(())
+    }
+
+    pub fn update_pair_price_feed(
+        ctx: Context<UpdatePairPriceFeed>,
+        base: String,
+        quote: String,
+        rate: u64,
+    ) -> Result<()> {
+        require!(
+            is_valid_asset_symbol(&base) && is_valid_asset_symbol(&quote) && base != quote,
+            ErrorCode::InvalidAssetPair
+        );
+        require!(rate > 0, ErrorCode::InvalidSwapInputs);
+        let price_feed = &mut ctx.accounts.price_feed;
+        price_feed.rate = rate;
+        price_feed.updated_at = Clock::get()?.unix_timestamp;
+        price_feed.bump = ctx.bumps.price_feed;
+
+        msg!("MXE: price feed {}/{} rate set to {}", base, quote, rate);
+        Ok(())
+    }
+
+    pub fn initialize_commitment_tree(ctx: Context<InitializeCommitmentTree>) -> Result<()> {
+        let tree = &mut ctx.accounts.commitment_tree;
+        tree.next_index = 0;
+        tree.frontier = [[0u8; 32]; COMMITMENT_TREE_DEPTH];
+        tree.root = empty_commitment_tree_root();
+        tree.bump = ctx.bumps.commitment_tree;
+
+        msg!(
+            "MXE: commitment tree initialized depth={}",
+            COMMITMENT_TREE_DEPTH
+        );
+        Ok(())
+    }
+
+    pub fn set_max_price_staleness_secs(
+        ctx: Context<UpdateMxeConfig>,
+        max_price_staleness_secs: u32,
+    ) -> Result<()> {
+        require!(
+            max_price_staleness_secs > 0,
+            ErrorCode::InvalidPriceStaleness
+        );
+        ctx.accounts.mxe_config.max_price_staleness_secs = max_price_staleness_secs;
+        Ok(())
+    }
+
+    pub fn set_soft_timeout_secs(
+        ctx: Context<UpdateMxeConfig>,
+        soft_timeout_secs: u32,
+    ) -> Result<()> {
+        require!(
+            soft_timeout_secs > 0 && soft_timeout_secs < ctx.accounts.mxe_config.hard_timeout_secs,
+            ErrorCode::InvalidSoftTimeout
+        );
+        ctx.accounts.mxe_config.soft_timeout_secs = soft_timeout_secs;
+        Ok(())
+    }
+
+    pub fn set_hard_timeout_secs(
+        ctx: Context<UpdateMxeConfig>,
+        hard_timeout_secs: u32,
+    ) -> Result<()> {
+        require!(
+            hard_timeout_secs > ctx.accounts.mxe_config.soft_timeout_secs,
+            ErrorCode::InvalidHardTimeout
+        );
+        ctx.accounts.mxe_config.hard_timeout_secs = hard_timeout_secs;
+        Ok(())
+    }
+
+    pub fn escalate_computation(
+        ctx: Context<EscalateComputation>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let soft_timeout_secs = ctx.accounts.mxe_config.soft_timeout_secs;
+        let computation = &mut ctx.accounts.computation;
+        require!(
+            computation.status == ComputationStatus::Queued,
+            ErrorCode::ComputationNotQueued
+        );
+        require!(
+            now >= computation.soft_timeout_at,
+            ErrorCode::SoftTimeoutNotReached
+        );
+
+        computation.priority = computation
+            .priority
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        computation.soft_timeout_at = now
+            .checked_add(soft_timeout_secs as i64)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(ComputationEscalated {
+            computation_offset,
+            requester: computation.requester,
+            priority: computation.priority,
+            next_soft_timeout_at: computation.soft_timeout_at,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    pub fn finalize_computation(
+        ctx: Context<FinalizeComputation>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let computation = &mut ctx.accounts.computation;
+        require!(
+            computation.status == ComputationStatus::Queued,
+            ErrorCode::ComputationNotQueued
+        );
+        computation.status = ComputationStatus::Finalized;
+        computation.finalized_at = Clock::get()?.unix_timestamp;
+        release_pending_slot(&mut ctx.accounts.mxe_config)?;
+
+        emit!(ComputationFinalized {
+            computation_offset,
+            requester: computation.requester,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn encrypt_bridge_amount_callback(
+        ctx: Context<ComputationCallback>,
+        computation_offset: u64,
+        output: Vec<u8>,
+    ) -> Result<()> {
+        let result_commitment = complete_computation(
+            &mut ctx.accounts.mxe_config,
+            &mut ctx.accounts.computation,
+            &ctx.accounts.instructions_sysvar,
+            ComputationKind::EncryptBridgeAmount,
+            &output,
+        )?;
+
+        emit!(BridgeAmountEncryptionCompleted {
+            computation_offset,
+            requester: ctx.accounts.computation.requester,
+            output,
+            result_commitment,
+            timestamp: ctx.accounts.computation.finalized_at,
+        });
+        Ok(())
+    }
+
+    pub fn calculate_swap_amount_callback(
+        ctx: Context<ComputationCallback>,
+        computation_offset: u64,
+        output: Vec<u8>,
+    ) -> Result<()> {
+        let result_commitment = complete_computation(
+            &mut ctx.accounts.mxe_config,
+            &mut ctx.accounts.computation,
+            &ctx.accounts.instructions_sysvar,
+            ComputationKind::CalculateSwapAmount,
+            &output,
+        )?;
+
+        emit!(SwapCalculationCompleted {
+            computation_offset,
+            requester: ctx.accounts.computation.requester,
+            output,
+            result_commitment,
+            timestamp: ctx.accounts.computation.finalized_at,
+        });
+        Ok(())
+    }
+
+    pub fn emit_audit_bundle(
+        ctx: Context<AuditComputation>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let computation = &ctx.accounts.computation;
+
+        emit!(AuditBundle {
+            computation_offset,
+            requester: computation.requester,
+            kind: computation.kind,
+            status: computation.status,
+            primary_commitment: computation.primary_commitment,
+            secondary_commitment: computation.secondary_commitment,
+            result_commitment: computation.result_commitment,
+            created_at: computation.created_at,
+            finalized_at: computation.finalized_at,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn open_bridge_amount(
+        ctx: Context<AuditComputation>,
+        computation_offset: u64,
+        amount: u64,
+        chain_fee: u64,
+        source_chain: String,
+        dest_chain: String,
+        user_pubkey: Pubkey,
+        nonce: [u8; 32],
+    ) -> Result<()> {
+        let computation = &ctx.accounts.computation;
+        let source_chain = normalize_chain(source_chain)?;
+        let dest_chain = normalize_chain(dest_chain)?;
+        let salt = derive_salt(&computation.requester, computation_offset);
+        require!(
+            commit_bridge_amount(
+                amount,
+                chain_fee,
+                &source_chain,
+                &dest_chain,
+                &user_pubkey,
+                &salt,
+                &nonce
+            ) == computation.primary_commitment,
+            ErrorCode::CommitmentMismatch
+        );
+
+        emit!(BridgeAmountOpened {
+            computation_offset,
+            requester: computation.requester,
+            user: user_pubkey,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn reclaim_computation(
+        ctx: Context<ReclaimComputation>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let hard_timeout_secs = ctx.accounts.mxe_config.hard_timeout_secs;
+        let computation = &mut ctx.accounts.computation;
+        require!(
+            computation.status == ComputationStatus::Queued,
+            ErrorCode::ComputationNotQueued
+        );
+        // Gated on the hard timeout, which always exceeds the soft one, so a
+        // stalled computation can be escalated before its requester gives up on it.
+        require!(
+            now >= computation
+                .created_at
+                .checked_add(hard_timeout_secs as i64)
+                .ok_or(ErrorCode::Overflow)?,
+            ErrorCode::HardTimeoutNotReached
+        );
+
+        // Tombstone rather than close: created_at stays set, so the offset can
+        // never be queued again and a late callback cannot land on a new request.
+        computation.status = ComputationStatus::Reclaimed;
+        release_pending_slot(&mut ctx.accounts.mxe_config)?;
+
+        msg!("MXE: reclaimed computation offset={}", computation_offset);
+        Ok(())
+    }
+
     pub fn init_encrypt_bridge_comp_def(ctx: Context<ComputationDefinition>) -> Result<()> {
-        emit_computation_def_event("encrypt_bridge_amount", ctx.accounts.payer.key())?;
+        emit_computation_def_event("encrypt_bridge_amount", ctx.accounts.authority.key())?;
         Ok(())
     }
 
     pub fn init_verify_tx_comp_def(ctx: Context<ComputationDefinition>) -> Result<()> {
-        emit_computation_def_event("verify_bridge_transaction", ctx.accounts.payer.key())?;
+        emit_computation_def_event("verify_bridge_transaction", ctx.accounts.authority.key())?;
         Ok(())
     }
 
     pub fn init_calculate_swap_comp_def(ctx: Context<ComputationDefinition>) -> Result<()> {
-        emit_computation_def_event("calculate_swap_amount", ctx.accounts.payer.key())?;
+        emit_computation_def_event("calculate_swap_amount", ctx.accounts.authority.key())?;
         Ok(())
     }
 
     pub fn init_encrypt_btc_comp_def(ctx: Context<ComputationDefinition>) -> Result<()> {
-        emit_computation_def_event("encrypt_btc_address", ctx.accounts.payer.key())?;
+        emit_computation_def_event("encrypt_btc_address", ctx.accounts.authority.key())?;
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn encrypt_bridge_amount(
-        ctx: Context<MpcOperation>,
+        ctx: Context<EncryptBridgeAmount>,
         computation_offset: u64,
         amount: u64,
         source_chain: String,
         dest_chain: String,
         user_pubkey: Pubkey,
+        expected_slippage_bps: Option<u16>,
+        nonce: [u8; 32],
     ) -> Result<()> {
         require!(amount > 0, ErrorCode::InvalidAmount);
-        let source_chain = normalize_chain(source_chain)?;
-        let dest_chain = normalize_chain(dest_chain)?;
+        require!(nonce != [0u8; 32], ErrorCode::InvalidNonce);
+        let expected_slippage_bps = expected_slippage_bps.unwrap_or(0);
+        require!(
+            expected_slippage_bps as u64 <= BPS_DENOMINATOR,
+            ErrorCode::InvalidSlippage
+        );
+        let source_chain = normalize_allowed_chain(&ctx.accounts.mxe_config, source_chain)?;
+        let dest_chain = normalize_allowed_chain(&ctx.accounts.mxe_config, dest_chain)?;
 
         let timestamp = Clock::get()?.unix_timestamp;
-        let amount_commitment =
-            commit_bridge_amount(amount, &source_chain, &dest_chain, &user_pubkey);
+        record_queued_computation(
+            &mut ctx.accounts.mxe_config,
+            &mut ctx.accounts.computation,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ComputationKind::EncryptBridgeAmount,
+            ctx.bumps.computation,
+            timestamp,
+        )?;
+        track_requester_offset(
+            &mut ctx.accounts.requester_state,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ctx.accounts.mxe_config.require_monotonic_offsets,
+            ctx.bumps.requester_state,
+        )?;
+        let computation_fee = collect_computation_fee(
+            &ctx.accounts.mxe_config,
+            &ctx.accounts.payer,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+        )?;
+        let chain_fee = configured_chain_fee(&ctx.accounts.chain_fee)?;
+        let salt = derive_salt(&ctx.accounts.payer.key(), computation_offset);
+        let amount_commitment = commit_bridge_amount(
+            amount,
+            chain_fee,
+            &source_chain,
+            &dest_chain,
+            &user_pubkey,
+            &salt,
+            &nonce,
+        );
+        let nonce_commitment = commitment(&nonce);
+        ctx.accounts.computation.primary_commitment = amount_commitment;
+        let leaf_index = ctx.accounts.commitment_tree.append(amount_commitment)?;
+        let commitment_root = ctx.accounts.commitment_tree.root;
+        // The estimate is advisory for clients; the commitment covers the amount only.
+        let estimated_min_out = estimate_min_out(amount, expected_slippage_bps)?;
 
         msg!(
             "MXE: encrypt_bridge_amount offset={} chains={}→{}",
@@ -60,6 +590,13 @@ pub mod flash_bridge_mxe {
             source_chain,
             dest_chain,
             amount_commitment,
+            nonce_commitment,
+            chain_fee,
+            expected_slippage_bps,
+            estimated_min_out,
+            leaf_index,
+            commitment_root,
+            computation_fee,
             computation_offset,
             timestamp,
         });
@@ -68,24 +605,52 @@ pub mod flash_bridge_mxe {
     }
 
     pub fn verify_bridge_transaction(
-        ctx: Context<MpcOperation>,
+        ctx: Context<VerifyBridgeTransaction>,
         computation_offset: u64,
         tx_hash: String,
         expected_amount: Vec<u8>,
         blockchain: String,
+        deadline: i64,
     ) -> Result<()> {
+        let timestamp = Clock::get()?.unix_timestamp;
+        check_deadline(timestamp, deadline, ctx.accounts.mxe_config.clock_skew_secs)?;
         let trimmed_hash = tx_hash.trim();
         require!(!trimmed_hash.is_empty(), ErrorCode::InvalidTxHash);
         require!(
-            expected_amount.len() >= MIN_CIPHERTEXT_BYTES
-                && expected_amount.len() <= MAX_CIPHERTEXT_BYTES,
+            ctx.accounts.mxe_config.accepts_ciphertext(&expected_amount),
             ErrorCode::InvalidEncryptedPayload
         );
-        let blockchain = normalize_chain(blockchain)?;
+        let blockchain = normalize_allowed_chain(&ctx.accounts.mxe_config, blockchain)?;
 
-        let timestamp = Clock::get()?.unix_timestamp;
+        record_queued_computation(
+            &mut ctx.accounts.mxe_config,
+            &mut ctx.accounts.computation,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ComputationKind::VerifyBridgeTransaction,
+            ctx.bumps.computation,
+            timestamp,
+        )?;
+        track_requester_offset(
+            &mut ctx.accounts.requester_state,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ctx.accounts.mxe_config.require_monotonic_offsets,
+            ctx.bumps.requester_state,
+        )?;
+        let computation_fee = collect_computation_fee(
+            &ctx.accounts.mxe_config,
+            &ctx.accounts.payer,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+        )?;
         let tx_hash_commitment = commitment(trimmed_hash.as_bytes());
         let expected_amount_commitment = commitment(&expected_amount);
+        let computation = &mut ctx.accounts.computation;
+        computation.primary_commitment = tx_hash_commitment;
+        computation.secondary_commitment = expected_amount_commitment;
+        let leaf_index = ctx.accounts.commitment_tree.append(tx_hash_commitment)?;
+        let commitment_root = ctx.accounts.commitment_tree.root;
 
         msg!(
             "MXE: verify_bridge_transaction offset={} chain={}",
@@ -96,6 +661,10 @@ pub mod flash_bridge_mxe {
         emit!(BridgeVerificationQueued {
             tx_hash_commitment,
             blockchain,
+            deadline,
+            leaf_index,
+            commitment_root,
+            computation_fee,
             computation_offset,
             expected_amount_commitment,
             timestamp,
@@ -105,35 +674,68 @@ pub mod flash_bridge_mxe {
     }
 
     pub fn calculate_swap_amount(
-        ctx: Context<MpcOperation>,
+        ctx: Context<CalculateSwapAmount>,
         computation_offset: u64,
         zen_amount: Vec<u8>,
-        exchange_rate: u64,
-        slippage_tolerance: u64,
+        slippage_bps: u16,
+        deadline: i64,
     ) -> Result<()> {
-        require!(exchange_rate > 0, ErrorCode::InvalidSwapInputs);
+        let timestamp = Clock::get()?.unix_timestamp;
+        check_deadline(timestamp, deadline, ctx.accounts.mxe_config.clock_skew_secs)?;
+        let exchange_rate = fresh_exchange_rate(
+            &ctx.accounts.price_feed,
+            &ctx.accounts.mxe_config,
+            timestamp,
+        )?;
+        let price_updated_at = ctx.accounts.price_feed.updated_at;
         require!(
-            slippage_tolerance <= MAX_SLIPPAGE_PERCENT,
+            is_slippage_allowed(&ctx.accounts.mxe_config, slippage_bps),
             ErrorCode::InvalidSwapInputs
         );
         require!(
-            zen_amount.len() >= MIN_CIPHERTEXT_BYTES && zen_amount.len() <= MAX_CIPHERTEXT_BYTES,
+            ctx.accounts.mxe_config.accepts_ciphertext(&zen_amount),
             ErrorCode::InvalidEncryptedPayload
         );
 
         let zen_commitment = commitment(&zen_amount);
         let zen_value = extract_u64_from_bytes(&zen_amount)?;
 
+        // Mirrors swap_breakdown in the circuit: the gross amount must fit in
+        // u64, the basis-point products are taken in u128
         let base_amount = zen_value
             .checked_mul(exchange_rate)
             .ok_or(ErrorCode::Overflow)?;
-        let slippage_penalty = base_amount
-            .checked_mul(slippage_tolerance)
-            .and_then(|value| value.checked_div(100))
-            .unwrap_or(0);
-        let sol_amount = base_amount.saturating_sub(slippage_penalty);
+        let slippage_penalty = apply_bps(base_amount as u128, slippage_bps)? as u64;
+        let after_slippage = base_amount - slippage_penalty;
+        let protocol_fee_bps = ctx.accounts.mxe_config.protocol_fee_bps;
+        let protocol_fee = apply_bps(after_slippage as u128, protocol_fee_bps)? as u64;
+        let sol_amount = after_slippage - protocol_fee;
+        let fee_breakdown_commitment =
+            commit_fee_breakdown(slippage_penalty, protocol_fee, sol_amount);
 
-        let timestamp = Clock::get()?.unix_timestamp;
+        record_queued_computation(
+            &mut ctx.accounts.mxe_config,
+            &mut ctx.accounts.computation,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ComputationKind::CalculateSwapAmount,
+            ctx.bumps.computation,
+            timestamp,
+        )?;
+        track_requester_offset(
+            &mut ctx.accounts.requester_state,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ctx.accounts.mxe_config.require_monotonic_offsets,
+            ctx.bumps.requester_state,
+        )?;
+        let computation_fee = collect_computation_fee(
+            &ctx.accounts.mxe_config,
+            &ctx.accounts.payer,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+        )?;
+        ctx.accounts.computation.primary_commitment = zen_commitment;
         msg!(
             "MXE: calculate_swap_amount offset={} chains ZEN->SOL",
             computation_offset
@@ -142,8 +744,13 @@ pub mod flash_bridge_mxe {
         emit!(SwapCalculationQueued {
             zen_amount_commitment: zen_commitment,
             exchange_rate,
-            slippage_tolerance,
+            price_updated_at,
+            slippage_bps,
+            protocol_fee_bps,
             sol_amount,
+            fee_breakdown_commitment,
+            deadline,
+            computation_fee,
             computation_offset,
             timestamp,
         });
@@ -151,97 +758,1541 @@ pub mod flash_bridge_mxe {
         Ok(())
     }
 
-    pub fn encrypt_btc_address(
+    /// Hop price feeds are passed as remaining accounts, one per hop in route order.
+    pub fn calculate_multihop_swap(
         ctx: Context<MpcOperation>,
         computation_offset: u64,
-        btc_address: String,
-        recipient_pubkey: Pubkey,
+        zen_amount: Vec<u8>,
+        route: SwapRoute,
+        deadline: i64,
     ) -> Result<()> {
+        let timestamp = Clock::get()?.unix_timestamp;
+        check_deadline(timestamp, deadline, ctx.accounts.mxe_config.clock_skew_secs)?;
         require!(
-            is_valid_btc_address(&btc_address),
-            ErrorCode::InvalidBtcAddress
+            !route.hops.is_empty() && route.hops.len() <= MAX_SWAP_HOPS,
+            ErrorCode::InvalidSwapRoute
+        );
+        require!(
+            route
+                .hops
+                .iter()
+                .all(|hop| is_slippage_allowed(&ctx.accounts.mxe_config, hop.slippage_bps)),
+            ErrorCode::InvalidSwapRoute
+        );
+        require!(is_connected_route(&route.hops), ErrorCode::InvalidSwapRoute);
+        require!(
+            ctx.accounts.mxe_config.accepts_ciphertext(&zen_amount),
+            ErrorCode::InvalidEncryptedPayload
+        );
+        require!(
+            ctx.remaining_accounts.len() == route.hops.len(),
+            ErrorCode::InvalidPriceFeed
         );
+        let mut hop_rates = Vec::with_capacity(route.hops.len());
+        let mut price_updated_at = i64::MAX;
+        for (hop, feed_account) in route.hops.iter().zip(ctx.remaining_accounts) {
+            let price_feed = pair_price_feed(feed_account, &hop.base, &hop.quote)?;
+            hop_rates.push(fresh_exchange_rate(
+                &price_feed,
+                &ctx.accounts.mxe_config,
+                timestamp,
+            )?);
+            price_updated_at = price_updated_at.min(price_feed.updated_at);
+        }
 
-        let timestamp = Clock::get()?.unix_timestamp;
-        let btc_address_commitment = commitment(btc_address.trim().as_bytes());
+        let zen_commitment = commitment(&zen_amount);
+        let zen_value = extract_u64_from_bytes(&zen_amount)?;
+
+        // Each hop converts at its own pair rate and then loses its own slippage;
+        // products are taken in u128 and every intermediate amount must fit in u64.
+        let mut amount = zen_value;
+        let mut hop_amounts = Vec::with_capacity(route.hops.len());
+        for (hop, &rate) in route.hops.iter().zip(&hop_rates) {
+            let gross =
+                u64::try_from(amount as u128 * rate as u128).map_err(|_| ErrorCode::Overflow)?;
+            amount = gross - apply_bps(gross as u128, hop.slippage_bps)? as u64;
+            hop_amounts.push(amount);
+        }
+        let protocol_fee_bps = ctx.accounts.mxe_config.protocol_fee_bps;
+        let sol_amount = amount - apply_bps(amount as u128, protocol_fee_bps)? as u64;
+
+        record_queued_computation(
+            &mut ctx.accounts.mxe_config,
+            &mut ctx.accounts.computation,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ComputationKind::CalculateMultihopSwap,
+            ctx.bumps.computation,
+            timestamp,
+        )?;
+        track_requester_offset(
+            &mut ctx.accounts.requester_state,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ctx.accounts.mxe_config.require_monotonic_offsets,
+            ctx.bumps.requester_state,
+        )?;
+        let computation_fee = collect_computation_fee(
+            &ctx.accounts.mxe_config,
+            &ctx.accounts.payer,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+        )?;
+        ctx.accounts.computation.primary_commitment = zen_commitment;
         msg!(
-            "MXE: encrypt_btc_address offset={} recipient={}",
+            "MXE: calculate_multihop_swap offset={} hops={}",
             computation_offset,
-            recipient_pubkey
+            route.hops.len()
         );
 
-        emit!(BtcAddressEncryptionQueued {
-            recipient: recipient_pubkey,
-            btc_address_commitment,
+        emit!(MultihopSwapCalculationQueued {
+            zen_amount_commitment: zen_commitment,
+            hop_rates,
+            price_updated_at,
+            hops: route.hops,
+            hop_amounts,
+            protocol_fee_bps,
+            sol_amount,
+            deadline,
+            computation_fee,
             computation_offset,
             timestamp,
         });
 
         Ok(())
     }
-}
 
-fn emit_computation_def_event(name: &str, authority: Pubkey) -> Result<()> {
-    emit!(ComputationDefinitionInitialized {
-        name: name.to_string(),
-        authority,
-        timestamp: Clock::get()?.unix_timestamp,
-    });
-    Ok(())
-}
+    pub fn compare_amounts(
+        ctx: Context<MpcOperation>,
+        computation_offset: u64,
+        a: Vec<u8>,
+        b: Vec<u8>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.mxe_config.accepts_ciphertext(&a)
+                && ctx.accounts.mxe_config.accepts_ciphertext(&b),
+            ErrorCode::InvalidEncryptedPayload
+        );
 
-fn extract_u64_from_bytes(bytes: &[u8]) -> Result<u64> {
-    if bytes.len() < MIN_CIPHERTEXT_BYTES {
-        return Err(error!(ErrorCode::InvalidEncryptedPayload));
-    }
-    let mut array = [0u8; 8];
-    array.copy_from_slice(&bytes[..8]);
+        let timestamp = Clock::get()?.unix_timestamp;
+        record_queued_computation(
+            &mut ctx.accounts.mxe_config,
+            &mut ctx.accounts.computation,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ComputationKind::CompareAmounts,
+            ctx.bumps.computation,
+            timestamp,
+        )?;
+        track_requester_offset(
+            &mut ctx.accounts.requester_state,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ctx.accounts.mxe_config.require_monotonic_offsets,
+            ctx.bumps.requester_state,
+        )?;
+        let computation_fee = collect_computation_fee(
+            &ctx.accounts.mxe_config,
+            &ctx.accounts.payer,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+        )?;
+        let a_commitment = commitment(&a);
+        let b_commitment = commitment(&b);
+        let computation = &mut ctx.accounts.computation;
+        computation.primary_commitment = a_commitment;
+        computation.secondary_commitment = b_commitment;
+
+        msg!("MXE: compare_amounts offset={}", computation_offset);
+
+        emit!(ComparisonQueued {
+            a_commitment,
+            b_commitment,
+            computation_fee,
+            computation_offset,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn net_bridge_batch(
+        ctx: Context<MpcOperation>,
+        computation_offset: u64,
+        encrypted_amounts: Vec<Vec<u8>>,
+    ) -> Result<()> {
+        require!(
+            !encrypted_amounts.is_empty() && encrypted_amounts.len() <= MAX_NETTING_BATCH,
+            ErrorCode::InvalidNettingBatch
+        );
+        require!(
+            encrypted_amounts
+                .iter()
+                .all(|amount| ctx.accounts.mxe_config.accepts_ciphertext(amount)),
+            ErrorCode::InvalidEncryptedPayload
+        );
+
+        // The amounts stay encrypted; the MXE nets them and the result
+        // arrives through the callback, so only the inputs are committed here.
+        let amount_commitments: Vec<[u8; 32]> = encrypted_amounts
+            .iter()
+            .map(|encrypted_amount| commitment(encrypted_amount))
+            .collect();
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        record_queued_computation(
+            &mut ctx.accounts.mxe_config,
+            &mut ctx.accounts.computation,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ComputationKind::NetBridgeBatch,
+            ctx.bumps.computation,
+            timestamp,
+        )?;
+        track_requester_offset(
+            &mut ctx.accounts.requester_state,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ctx.accounts.mxe_config.require_monotonic_offsets,
+            ctx.bumps.requester_state,
+        )?;
+        let computation_fee = collect_computation_fee(
+            &ctx.accounts.mxe_config,
+            &ctx.accounts.payer,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+        )?;
+        let batch_commitment = commit_netting_batch(&amount_commitments);
+        ctx.accounts.computation.primary_commitment = batch_commitment;
+
+        msg!(
+            "MXE: net_bridge_batch offset={} count={}",
+            computation_offset,
+            amount_commitments.len()
+        );
+
+        emit!(BatchNettingQueued {
+            amount_commitments,
+            batch_commitment,
+            computation_fee,
+            computation_offset,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn check_reportable(
+        ctx: Context<MpcOperation>,
+        computation_offset: u64,
+        encrypted_amount: Vec<u8>,
+        threshold: u64,
+    ) -> Result<()> {
+        require!(threshold > 0, ErrorCode::InvalidAmount);
+        require!(
+            ctx.accounts
+                .mxe_config
+                .accepts_ciphertext(&encrypted_amount),
+            ErrorCode::InvalidEncryptedPayload
+        );
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        record_queued_computation(
+            &mut ctx.accounts.mxe_config,
+            &mut ctx.accounts.computation,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ComputationKind::CheckReportable,
+            ctx.bumps.computation,
+            timestamp,
+        )?;
+        track_requester_offset(
+            &mut ctx.accounts.requester_state,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ctx.accounts.mxe_config.require_monotonic_offsets,
+            ctx.bumps.requester_state,
+        )?;
+        let computation_fee = collect_computation_fee(
+            &ctx.accounts.mxe_config,
+            &ctx.accounts.payer,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+        )?;
+        let amount_commitment = commitment(&encrypted_amount);
+        let computation = &mut ctx.accounts.computation;
+        computation.primary_commitment = amount_commitment;
+        computation.secondary_commitment = commitment(&threshold.to_le_bytes());
+
+        msg!(
+            "MXE: check_reportable offset={} threshold={}",
+            computation_offset,
+            threshold
+        );
+
+        emit!(ReportableCheckQueued {
+            amount_commitment,
+            threshold,
+            computation_fee,
+            computation_offset,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn verify_btc_address_allowed(
+        ctx: Context<MpcOperation>,
+        computation_offset: u64,
+        encrypted_address: Vec<u8>,
+        allowlist_root: [u8; 32],
+    ) -> Result<()> {
+        require!(allowlist_root != [0u8; 32], ErrorCode::InvalidAllowlistRoot);
+        require!(
+            ctx.accounts
+                .mxe_config
+                .accepts_ciphertext(&encrypted_address),
+            ErrorCode::InvalidEncryptedPayload
+        );
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        record_queued_computation(
+            &mut ctx.accounts.mxe_config,
+            &mut ctx.accounts.computation,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ComputationKind::VerifyBtcAddressAllowed,
+            ctx.bumps.computation,
+            timestamp,
+        )?;
+        track_requester_offset(
+            &mut ctx.accounts.requester_state,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ctx.accounts.mxe_config.require_monotonic_offsets,
+            ctx.bumps.requester_state,
+        )?;
+        let computation_fee = collect_computation_fee(
+            &ctx.accounts.mxe_config,
+            &ctx.accounts.payer,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+        )?;
+        let address_commitment = commitment(&encrypted_address);
+        let computation = &mut ctx.accounts.computation;
+        computation.primary_commitment = address_commitment;
+        computation.secondary_commitment = allowlist_root;
+
+        msg!(
+            "MXE: verify_btc_address_allowed offset={}",
+            computation_offset
+        );
+
+        emit!(AddressAllowlistCheckQueued {
+            address_commitment,
+            allowlist_root,
+            computation_fee,
+            computation_offset,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn verify_commitment_batch(
+        _ctx: Context<VerifyCommitmentBatch>,
+        root: [u8; 32],
+        leaves: Vec<[u8; 32]>,
+        proofs: Vec<Vec<[u8; 32]>>,
+    ) -> Result<()> {
+        require!(
+            !leaves.is_empty() && leaves.len() <= MAX_COMMITMENT_BATCH,
+            ErrorCode::InvalidCommitmentBatch
+        );
+        require!(
+            leaves.len() == proofs.len(),
+            ErrorCode::InvalidCommitmentBatch
+        );
+
+        for (leaf, proof) in leaves.iter().zip(proofs.iter()) {
+            require!(
+                is_committed(*leaf, proof, &root),
+                ErrorCode::InvalidMerkleProof
+            );
+        }
+
+        emit!(CommitmentBatchVerified {
+            root,
+            verified_count: leaves.len() as u32,
+            total: leaves.len() as u32,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn encrypt_btc_address(
+        ctx: Context<EncryptBtcAddress>,
+        computation_offset: u64,
+        btc_address: String,
+        recipient_pubkey: Pubkey,
+    ) -> Result<()> {
+        require!(
+            is_valid_btc_address(&btc_address, ctx.accounts.mxe_config.allow_testnet_btc),
+            ErrorCode::InvalidBtcAddress
+        );
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        record_queued_computation(
+            &mut ctx.accounts.mxe_config,
+            &mut ctx.accounts.computation,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ComputationKind::EncryptBtcAddress,
+            ctx.bumps.computation,
+            timestamp,
+        )?;
+        track_requester_offset(
+            &mut ctx.accounts.requester_state,
+            ctx.accounts.payer.key(),
+            computation_offset,
+            ctx.accounts.mxe_config.require_monotonic_offsets,
+            ctx.bumps.requester_state,
+        )?;
+        let computation_fee = collect_computation_fee(
+            &ctx.accounts.mxe_config,
+            &ctx.accounts.payer,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+        )?;
+        let btc_address_commitment = commitment(btc_address.trim().as_bytes());
+        ctx.accounts.computation.primary_commitment = btc_address_commitment;
+
+        let encryption = &mut ctx.accounts.btc_address_encryption;
+        encryption.recipient = recipient_pubkey;
+        encryption.btc_address_commitment = btc_address_commitment;
+        encryption.computation_offset = computation_offset;
+        encryption.created_at = timestamp;
+        encryption.bump = ctx.bumps.btc_address_encryption;
+
+        msg!(
+            "MXE: encrypt_btc_address offset={} recipient={}",
+            computation_offset,
+            recipient_pubkey
+        );
+
+        emit!(BtcAddressEncryptionQueued {
+            recipient: recipient_pubkey,
+            btc_address_commitment,
+            computation_fee,
+            computation_offset,
+            timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+fn emit_computation_def_event(name: &str, authority: Pubkey) -> Result<()> {
+    emit!(ComputationDefinitionInitialized {
+        name: name.to_string(),
+        authority,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+fn record_queued_computation(
+    config: &mut MxeConfig,
+    computation: &mut Computation,
+    requester: Pubkey,
+    computation_offset: u64,
+    kind: ComputationKind,
+    bump: u8,
+    timestamp: i64,
+) -> Result<()> {
+    // The account is created on first use, so a non-zero created_at means the offset is taken.
+    require!(
+        computation.created_at == 0,
+        ErrorCode::DuplicateComputationOffset
+    );
+    require!(
+        config.pending_computations < config.max_pending_computations,
+        ErrorCode::QueueFull
+    );
+    config.pending_computations = config
+        .pending_computations
+        .checked_add(1)
+        .ok_or(ErrorCode::Overflow)?;
+
+    computation.requester = requester;
+    computation.computation_offset = computation_offset;
+    computation.status = ComputationStatus::Queued;
+    computation.kind = kind;
+    computation.created_at = timestamp;
+    computation.soft_timeout_at = timestamp
+        .checked_add(config.soft_timeout_secs as i64)
+        .ok_or(ErrorCode::Overflow)?;
+    computation.priority = 0;
+    computation.finalized_at = 0;
+    computation.result_commitment = [0u8; 32];
+    computation.bump = bump;
+    Ok(())
+}
+
+fn track_requester_offset(
+    state: &mut RequesterState,
+    requester: Pubkey,
+    computation_offset: u64,
+    require_monotonic_offsets: bool,
+    bump: u8,
+) -> Result<()> {
+    if state.computations_queued > 0 && require_monotonic_offsets {
+        require!(
+            computation_offset > state.last_offset,
+            ErrorCode::NonMonotonicOffset
+        );
+    }
+    state.requester = requester;
+    state.last_offset = computation_offset;
+    state.computations_queued = state
+        .computations_queued
+        .checked_add(1)
+        .ok_or(ErrorCode::Overflow)?;
+    state.bump = bump;
+    Ok(())
+}
+
+// The seeds constraint pins the address, so an uninitialized account can only
+// mean the authority never set a fee for this pair.
+fn configured_chain_fee(chain_fee: &AccountInfo) -> Result<u64> {
+    if chain_fee.owner != &crate::ID || chain_fee.data_is_empty() {
+        return Ok(0);
+    }
+    Ok(ChainFee::try_deserialize(&mut &chain_fee.try_borrow_data()?[..])?.fee)
+}
+
+fn collect_computation_fee<'info>(
+    config: &MxeConfig,
+    payer: &Signer<'info>,
+    treasury: &SystemAccount<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<u64> {
+    let fee = config.computation_fee;
+    if fee == 0 {
+        return Ok(0);
+    }
+    require!(
+        payer.lamports() >= fee,
+        ErrorCode::InsufficientComputationFee
+    );
+    // A transfer that leaves an empty treasury below the rent-exempt minimum fails at runtime
+    let treasury_balance = treasury
+        .lamports()
+        .checked_add(fee)
+        .ok_or(ErrorCode::Overflow)?;
+    require!(
+        Rent::get()?.is_exempt(treasury_balance, 0),
+        ErrorCode::TreasuryNotRentExempt
+    );
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer.to_account_info(),
+                to: treasury.to_account_info(),
+            },
+        ),
+        fee,
+    )?;
+    Ok(fee)
+}
+
+// Callbacks are only valid as a CPI from the Arcium program, which is then the
+// top-level instruction being executed.
+fn complete_computation(
+    config: &mut MxeConfig,
+    computation: &mut Computation,
+    instructions_sysvar: &AccountInfo,
+    kind: ComputationKind,
+    output: &[u8],
+) -> Result<[u8; 32]> {
+    let current_ix = sysvar_instructions::get_instruction_relative(0, instructions_sysvar)?;
+    require_keys_eq!(
+        current_ix.program_id,
+        config.arcium_program,
+        ErrorCode::UnauthorizedCallback
+    );
+    require!(
+        computation.status == ComputationStatus::Queued,
+        ErrorCode::ComputationNotQueued
+    );
+    require!(computation.kind == kind, ErrorCode::ComputationKindMismatch);
+    require!(!output.is_empty(), ErrorCode::InvalidEncryptedPayload);
+
+    let result_commitment = commitment(output);
+    computation.status = ComputationStatus::Finalized;
+    computation.finalized_at = Clock::get()?.unix_timestamp;
+    computation.result_commitment = result_commitment;
+    release_pending_slot(config)?;
+    Ok(result_commitment)
+}
+
+fn release_pending_slot(config: &mut MxeConfig) -> Result<()> {
+    config.pending_computations = config
+        .pending_computations
+        .checked_sub(1)
+        .ok_or(ErrorCode::Overflow)?;
+    Ok(())
+}
+
+fn extract_u64_from_bytes(bytes: &[u8]) -> Result<u64> {
+    if bytes.len() < MIN_CIPHERTEXT_BYTES {
+        return Err(error!(ErrorCode::InvalidEncryptedPayload));
+    }
+    let mut array = [0u8; 8];
+    array.copy_from_slice(&bytes[..8]);
     Ok(u64::from_le_bytes(array))
 }
 
-fn is_valid_btc_address(address: &str) -> bool {
-    let len = address.len();
-    len >= 26 && len <= 62 && !address.contains(' ')
+fn check_deadline(now: i64, deadline: i64, clock_skew_secs: u32) -> Result<()> {
+    require!(
+        now.saturating_sub(clock_skew_secs as i64) <= deadline,
+        ErrorCode::DeadlineExpired
+    );
+    Ok(())
+}
+
+// Hop feeds arrive as remaining accounts, so repeat the checks Anchor would make:
+// program ownership, the account discriminator and the pair's PDA.
+fn pair_price_feed(feed_account: &AccountInfo, base: &str, quote: &str) -> Result<PriceFeed> {
+    require!(
+        feed_account.owner == &crate::ID,
+        ErrorCode::InvalidPriceFeed
+    );
+    let price_feed = PriceFeed::try_deserialize(&mut &feed_account.try_borrow_data()?[..])?;
+    let expected = Pubkey::create_program_address(
+        &[
+            PRICE_FEED_SEED,
+            asset_seed(base),
+            asset_seed(quote),
+            &[price_feed.bump],
+        ],
+        &crate::ID,
+    )
+    .map_err(|_| error!(ErrorCode::InvalidPriceFeed))?;
+    require!(feed_account.key() == expected, ErrorCode::InvalidPriceFeed);
+    Ok(price_feed)
+}
+
+fn fresh_exchange_rate(price_feed: &PriceFeed, config: &MxeConfig, now: i64) -> Result<u64> {
+    require!(
+        now.saturating_sub(price_feed.updated_at) <= config.max_price_staleness_secs as i64,
+        ErrorCode::StalePrice
+    );
+    require!(price_feed.rate > 0, ErrorCode::InvalidSwapInputs);
+    Ok(price_feed.rate)
+}
+
+fn is_valid_asset_symbol(symbol: &str) -> bool {
+    (1..=MAX_ASSET_SYMBOL_LEN).contains(&symbol.len())
+        && symbol
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+}
+
+// Invalid symbols share one fixed seed so the PDA derivation stays within the
+// seed limit; the handler then rejects them with InvalidAssetPair.
+fn asset_seed(symbol: &str) -> &[u8] {
+    if is_valid_asset_symbol(symbol) {
+        symbol.as_bytes()
+    } else {
+        UNSUPPORTED_ASSET_SEED
+    }
+}
+
+// Hops must chain ZEN -> ... -> SOL, each quote being the next hop's base
+fn is_connected_route(hops: &[SwapHop]) -> bool {
+    let (Some(first), Some(last)) = (hops.first(), hops.last()) else {
+        return false;
+    };
+    first.base == SWAP_SOURCE_ASSET
+        && last.quote == SWAP_DEST_ASSET
+        && hops
+            .iter()
+            .all(|hop| is_valid_asset_symbol(&hop.base) && is_valid_asset_symbol(&hop.quote))
+        && hops.windows(2).all(|pair| pair[0].quote == pair[1].base)
+}
+
+fn is_slippage_allowed(config: &MxeConfig, slippage_bps: u16) -> bool {
+    slippage_bps <= config.max_slippage_bps
+}
+
+// bps is at most BPS_DENOMINATOR, so the result never exceeds amount
+fn apply_bps(amount: u128, bps: u16) -> Result<u128> {
+    Ok(amount.checked_mul(bps as u128).ok_or(ErrorCode::Overflow)? / BPS_DENOMINATOR as u128)
+}
+
+fn is_valid_btc_address(address: &str, allow_testnet: bool) -> bool {
+    if !(MIN_BTC_ADDRESS_LEN..=MAX_BTC_ADDRESS_LEN).contains(&address.len())
+        || address.chars().any(|c| c.is_whitespace())
+    {
+        return false;
+    }
+    let lower = address.to_ascii_lowercase();
+    if lower.starts_with("bc1") || lower.starts_with("tb1") {
+        is_valid_segwit_address(address, allow_testnet)
+    } else {
+        is_valid_base58_address(address, allow_testnet)
+    }
+}
+
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BECH32_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32_CONST: u32 = 1;
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+
+// P2PKH / P2SH version bytes for mainnet, then testnet
+const BASE58_MAINNET_VERSIONS: [u8; 2] = [0x00, 0x05];
+const BASE58_TESTNET_VERSIONS: [u8; 2] = [0x6f, 0xc4];
+
+fn is_valid_base58_address(address: &str, allow_testnet: bool) -> bool {
+    let Some(decoded) = base58_decode(address) else {
+        return false;
+    };
+    if decoded.len() != 25 {
+        return false;
+    }
+    let (payload, checksum) = decoded.split_at(21);
+    if hash::hash(&hash::hash(payload).to_bytes()).to_bytes()[..4] != *checksum {
+        return false;
+    }
+    BASE58_MAINNET_VERSIONS.contains(&payload[0])
+        || (allow_testnet && BASE58_TESTNET_VERSIONS.contains(&payload[0]))
+}
+
+fn base58_decode(input: &str) -> Option<Vec<u8>> {
+    // Little-endian base-256 accumulator
+    let mut bytes: Vec<u8> = Vec::with_capacity(input.len());
+    for c in input.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|&a| a == c)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let leading_zeros = input.bytes().take_while(|&c| c == b'1').count();
+    bytes.resize(bytes.len() + leading_zeros, 0);
+    bytes.reverse();
+    Some(bytes)
+}
+
+fn is_valid_segwit_address(address: &str, allow_testnet: bool) -> bool {
+    if address.chars().any(|c| c.is_ascii_lowercase())
+        && address.chars().any(|c| c.is_ascii_uppercase())
+    {
+        return false;
+    }
+    let address = address.to_ascii_lowercase();
+    let Some(separator) = address.rfind('1') else {
+        return false;
+    };
+    let (hrp, data) = (&address[..separator], &address[separator + 1..]);
+    if !(hrp == "bc" || (allow_testnet && hrp == "tb")) {
+        return false;
+    }
+
+    let Some(values) = data
+        .bytes()
+        .map(|c| BECH32_CHARSET.iter().position(|&a| a == c).map(|v| v as u8))
+        .collect::<Option<Vec<u8>>>()
+    else {
+        return false;
+    };
+    // witness version + checksum, with a 2..=40 byte witness program
+    if values.len() < 7 {
+        return false;
+    }
+    let program_bytes = (values.len() - 7) * 5 / 8;
+    let witness_version = values[0];
+    let expected_const = match witness_version {
+        0 if program_bytes == 20 || program_bytes == 32 => BECH32_CONST,
+        1..=16 if (2..=40).contains(&program_bytes) => BECH32M_CONST,
+        _ => return false,
+    };
+    // BIP-173: the 5-to-8-bit conversion may leave at most 4 padding bits, all zero
+    let program = &values[1..values.len() - 6];
+    let padding_bits = program.len() * 5 % 8;
+    if padding_bits > 4 || program[program.len() - 1] & ((1 << padding_bits) - 1) != 0 {
+        return false;
+    }
+
+    let mut checked = bech32_hrp_expand(hrp);
+    checked.extend_from_slice(&values);
+    bech32_polymod(&checked) == expected_const
+}
+
+fn bech32_hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut expanded: Vec<u8> = hrp.bytes().map(|c| c >> 5).collect();
+    expanded.push(0);
+    expanded.extend(hrp.bytes().map(|c| c & 31));
+    expanded
+}
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+    values.iter().fold(1u32, |checksum, &value| {
+        let top = checksum >> 25;
+        let mut checksum = ((checksum & 0x01ff_ffff) << 5) ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+        checksum
+    })
 }
 
 fn commitment(data: &[u8]) -> [u8; 32] {
     keccak::hash(data).to_bytes()
 }
 
+// Bech32 is case-insensitive, so segwit addresses are hashed in lowercase to keep
+// one record per address; base58 is case-sensitive and is hashed as given.
+fn btc_address_encryption_key(recipient: &Pubkey, btc_address: &str) -> [u8; 32] {
+    let address = btc_address.trim();
+    let lower = address.to_ascii_lowercase();
+    let canonical = if lower.starts_with("bc1") || lower.starts_with("tb1") {
+        lower.as_str()
+    } else {
+        address
+    };
+    let mut buffer = Vec::with_capacity(64);
+    buffer.extend_from_slice(recipient.as_ref());
+    buffer.extend_from_slice(&commitment(canonical.as_bytes()));
+    commitment(&buffer)
+}
+
+/// Salt for commitments owned by `requester` at `offset`, reproducible off-chain
+/// as the first 16 bytes of `keccak(requester || offset_le)`.
+pub fn derive_salt(requester: &Pubkey, offset: u64) -> [u8; 16] {
+    let hash = keccak::hashv(&[requester.as_ref(), &offset.to_le_bytes()]).to_bytes();
+    let mut salt = [0u8; 16];
+    salt.copy_from_slice(&hash[..16]);
+    salt
+}
+
 fn commit_bridge_amount(
     amount: u64,
+    chain_fee: u64,
     source_chain: &str,
     dest_chain: &str,
     user: &Pubkey,
+    salt: &[u8; 16],
+    nonce: &[u8; 32],
 ) -> [u8; 32] {
-    let mut buffer = Vec::with_capacity(8 + source_chain.len() + dest_chain.len() + 32);
+    let mut buffer = Vec::with_capacity(16 + source_chain.len() + dest_chain.len() + 32 + 16 + 32);
     buffer.extend_from_slice(&amount.to_le_bytes());
+    buffer.extend_from_slice(&chain_fee.to_le_bytes());
     buffer.extend_from_slice(source_chain.as_bytes());
     buffer.extend_from_slice(dest_chain.as_bytes());
     buffer.extend_from_slice(user.as_ref());
+    buffer.extend_from_slice(salt);
+    buffer.extend_from_slice(nonce);
     commitment(&buffer)
 }
 
+fn estimate_min_out(amount: u64, slippage_bps: u16) -> Result<u64> {
+    let kept_bps = BPS_DENOMINATOR - slippage_bps as u64;
+    let estimate = (amount as u128)
+        .checked_mul(kept_bps as u128)
+        .ok_or(ErrorCode::Overflow)?
+        / BPS_DENOMINATOR as u128;
+    u64::try_from(estimate).map_err(|_| error!(ErrorCode::Overflow))
+}
+
+fn commit_netting_batch(amount_commitments: &[[u8; 32]]) -> [u8; 32] {
+    let leaves: Vec<&[u8]> = amount_commitments
+        .iter()
+        .map(|leaf| leaf.as_slice())
+        .collect();
+    keccak::hashv(&leaves).to_bytes()
+}
+
+/// Recomputes a root from the commitment `leaf` and its sibling path,
+/// bottom-up. Pairs are hashed in sorted order, so the path needs no
+/// left/right flags; this is how clients check a `CommitmentTree` inclusion
+/// proof off-chain.
+pub fn merkle_root_from_proof(leaf: [u8; 32], proof: &[[u8; 32]]) -> [u8; 32] {
+    proof.iter().fold(hash_merkle_leaf(&leaf), |node, sibling| {
+        hash_merkle_pair(&node, sibling)
+    })
+}
+
+// Only full-depth paths are accepted; a shorter path would let an interior
+// node or the root itself pass as a commitment.
+fn is_committed(leaf: [u8; 32], proof: &[[u8; 32]], root: &[u8; 32]) -> bool {
+    proof.len() == COMMITMENT_TREE_DEPTH && merkle_root_from_proof(leaf, proof) == *root
+}
+
+fn empty_commitment_tree_root() -> [u8; 32] {
+    (0..COMMITMENT_TREE_DEPTH).fold([0u8; 32], |zero, _| hash_merkle_pair(&zero, &zero))
+}
+
+fn hash_merkle_leaf(leaf: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[MERKLE_LEAF_PREFIX, leaf]).to_bytes()
+}
+
+fn hash_merkle_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    keccak::hashv(&[MERKLE_NODE_PREFIX, left, right]).to_bytes()
+}
+
+fn commit_fee_breakdown(slippage_penalty: u64, protocol_fee: u64, net_out: u64) -> [u8; 32] {
+    let mut buffer = [0u8; 24];
+    buffer[..8].copy_from_slice(&slippage_penalty.to_le_bytes());
+    buffer[8..16].copy_from_slice(&protocol_fee.to_le_bytes());
+    buffer[16..].copy_from_slice(&net_out.to_le_bytes());
+    commitment(&buffer)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Chain {
+    Btc,
+    Zec,
+    Sol,
+    Eth,
+}
+
+impl Chain {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Chain::Btc => "BTC",
+            Chain::Zec => "ZEC",
+            Chain::Sol => "SOL",
+            Chain::Eth => "ETH",
+        }
+    }
+}
+
+impl std::str::FromStr for Chain {
+    type Err = Error;
+
+    fn from_str(chain: &str) -> Result<Self> {
+        match chain.trim().to_ascii_uppercase().as_str() {
+            "BTC" | "BITCOIN" => Ok(Chain::Btc),
+            "ZEC" | "ZCASH" => Ok(Chain::Zec),
+            "SOL" | "SOLANA" => Ok(Chain::Sol),
+            "ETH" | "ETHEREUM" => Ok(Chain::Eth),
+            _ => Err(error!(ErrorCode::UnsupportedChain)),
+        }
+    }
+}
+
+// Unknown chains all map to one fixed seed, so arbitrary caller input can never
+// exceed the 32-byte seed limit; the handler then rejects them with UnsupportedChain.
+fn chain_seed(chain: &str) -> &'static [u8] {
+    chain
+        .parse::<Chain>()
+        .map_or(UNSUPPORTED_CHAIN_SEED, |chain| chain.as_str().as_bytes())
+}
+
+fn normalize_allowed_chain(config: &MxeConfig, chain: String) -> Result<String> {
+    let chain = normalize_chain(chain)?;
+    require!(config.is_chain_allowed(&chain), ErrorCode::ChainNotAllowed);
+    Ok(chain)
+}
+
 fn normalize_chain(chain: String) -> Result<String> {
     let trimmed = chain.trim();
     require!(!trimmed.is_empty(), ErrorCode::MissingChainInfo);
-    require!(
-        trimmed.len() <= MAX_CHAIN_NAME_LEN,
-        ErrorCode::MissingChainInfo
-    );
-    Ok(trimmed.to_ascii_uppercase())
+    Ok(trimmed.parse::<Chain>()?.as_str().to_string())
 }
 
 #[derive(Accounts)]
 pub struct ComputationDefinition<'info> {
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [MXE_CONFIG_SEED],
+        bump = mxe_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub mxe_config: Account<'info, MxeConfig>,
 }
 
 #[derive(Accounts)]
+#[instruction(computation_offset: u64)]
 pub struct MpcOperation<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [MXE_CONFIG_SEED],
+        bump = mxe_config.bump,
+        constraint = !mxe_config.paused @ ErrorCode::BridgeInactive,
+        constraint = mxe_config.is_operator(&payer.key()) @ ErrorCode::UnauthorizedOperator
+    )]
+    pub mxe_config: Account<'info, MxeConfig>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + Computation::INIT_SPACE,
+        seeds = [COMPUTATION_SEED, &computation_offset.to_le_bytes()],
+        bump
+    )]
+    pub computation: Account<'info, Computation>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + RequesterState::INIT_SPACE,
+        seeds = [REQUESTER_STATE_SEED, payer.key().as_ref()],
+        bump
+    )]
+    pub requester_state: Account<'info, RequesterState>,
+    #[account(mut, address = mxe_config.treasury)]
+    pub treasury: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct CalculateSwapAmount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [MXE_CONFIG_SEED],
+        bump = mxe_config.bump,
+        constraint = !mxe_config.paused @ ErrorCode::BridgeInactive,
+        constraint = mxe_config.is_operator(&payer.key()) @ ErrorCode::UnauthorizedOperator
+    )]
+    pub mxe_config: Account<'info, MxeConfig>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + Computation::INIT_SPACE,
+        seeds = [COMPUTATION_SEED, &computation_offset.to_le_bytes()],
+        bump
+    )]
+    pub computation: Account<'info, Computation>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + RequesterState::INIT_SPACE,
+        seeds = [REQUESTER_STATE_SEED, payer.key().as_ref()],
+        bump
+    )]
+    pub requester_state: Account<'info, RequesterState>,
+    #[account(seeds = [PRICE_FEED_SEED], bump = price_feed.bump)]
+    pub price_feed: Account<'info, PriceFeed>,
+    #[account(mut, address = mxe_config.treasury)]
+    pub treasury: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct VerifyBridgeTransaction<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [MXE_CONFIG_SEED],
+        bump = mxe_config.bump,
+        constraint = !mxe_config.paused @ ErrorCode::BridgeInactive,
+        constraint = mxe_config.is_operator(&payer.key()) @ ErrorCode::UnauthorizedOperator
+    )]
+    pub mxe_config: Account<'info, MxeConfig>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + Computation::INIT_SPACE,
+        seeds = [COMPUTATION_SEED, &computation_offset.to_le_bytes()],
+        bump
+    )]
+    pub computation: Account<'info, Computation>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + RequesterState::INIT_SPACE,
+        seeds = [REQUESTER_STATE_SEED, payer.key().as_ref()],
+        bump
+    )]
+    pub requester_state: Account<'info, RequesterState>,
+    #[account(mut, seeds = [COMMITMENT_TREE_SEED], bump = commitment_tree.bump)]
+    pub commitment_tree: Account<'info, CommitmentTree>,
+    #[account(mut, address = mxe_config.treasury)]
+    pub treasury: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, amount: u64, source_chain: String, dest_chain: String)]
+pub struct EncryptBridgeAmount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [MXE_CONFIG_SEED],
+        bump = mxe_config.bump,
+        constraint = !mxe_config.paused @ ErrorCode::BridgeInactive,
+        constraint = mxe_config.is_operator(&payer.key()) @ ErrorCode::UnauthorizedOperator
+    )]
+    pub mxe_config: Account<'info, MxeConfig>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + Computation::INIT_SPACE,
+        seeds = [COMPUTATION_SEED, &computation_offset.to_le_bytes()],
+        bump
+    )]
+    pub computation: Account<'info, Computation>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + RequesterState::INIT_SPACE,
+        seeds = [REQUESTER_STATE_SEED, payer.key().as_ref()],
+        bump
+    )]
+    pub requester_state: Account<'info, RequesterState>,
+    /// CHECK: the pair's ChainFee PDA; it may not exist yet, which means no fee.
+    #[account(
+        seeds = [CHAIN_FEE_SEED, chain_seed(&source_chain), chain_seed(&dest_chain)],
+        bump
+    )]
+    pub chain_fee: UncheckedAccount<'info>,
+    #[account(mut, seeds = [COMMITMENT_TREE_SEED], bump = commitment_tree.bump)]
+    pub commitment_tree: Account<'info, CommitmentTree>,
+    #[account(mut, address = mxe_config.treasury)]
+    pub treasury: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, btc_address: String, recipient_pubkey: Pubkey)]
+pub struct EncryptBtcAddress<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [MXE_CONFIG_SEED],
+        bump = mxe_config.bump,
+        constraint = !mxe_config.paused @ ErrorCode::BridgeInactive,
+        constraint = mxe_config.is_operator(&payer.key()) @ ErrorCode::UnauthorizedOperator
+    )]
+    pub mxe_config: Account<'info, MxeConfig>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + Computation::INIT_SPACE,
+        seeds = [COMPUTATION_SEED, &computation_offset.to_le_bytes()],
+        bump
+    )]
+    pub computation: Account<'info, Computation>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + RequesterState::INIT_SPACE,
+        seeds = [REQUESTER_STATE_SEED, payer.key().as_ref()],
+        bump
+    )]
+    pub requester_state: Account<'info, RequesterState>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + BtcAddressEncryption::INIT_SPACE,
+        seeds = [
+            BTC_ADDRESS_ENCRYPTION_SEED,
+            &btc_address_encryption_key(&recipient_pubkey, &btc_address)
+        ],
+        bump
+    )]
+    pub btc_address_encryption: Account<'info, BtcAddressEncryption>,
+    #[account(mut, address = mxe_config.treasury)]
+    pub treasury: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyCommitmentBatch {}
+
+#[derive(Accounts)]
+pub struct InitializeMxeConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MxeConfig::INIT_SPACE,
+        seeds = [MXE_CONFIG_SEED],
+        bump
+    )]
+    pub mxe_config: Account<'info, MxeConfig>,
+    // Only the upgrade authority may claim the config, so a deploy cannot be front-run
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::FlashBridgeMxe>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMxeConfig<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [MXE_CONFIG_SEED],
+        bump = mxe_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub mxe_config: Account<'info, MxeConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(source_chain: String, dest_chain: String)]
+pub struct SetChainFee<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump, has_one = authority @ ErrorCode::Unauthorized)]
+    pub mxe_config: Account<'info, MxeConfig>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ChainFee::INIT_SPACE,
+        seeds = [CHAIN_FEE_SEED, chain_seed(&source_chain), chain_seed(&dest_chain)],
+        bump
+    )]
+    pub chain_fee: Account<'info, ChainFee>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePriceFeed<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump, has_one = authority @ ErrorCode::Unauthorized)]
+    pub mxe_config: Account<'info, MxeConfig>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PriceFeed::INIT_SPACE,
+        seeds = [PRICE_FEED_SEED],
+        bump
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(base: String, quote: String)]
+pub struct UpdatePairPriceFeed<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump, has_one = authority @ ErrorCode::Unauthorized)]
+    pub mxe_config: Account<'info, MxeConfig>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PriceFeed::INIT_SPACE,
+        seeds = [PRICE_FEED_SEED, asset_seed(&base), asset_seed(&quote)],
+        bump
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCommitmentTree<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump, has_one = authority @ ErrorCode::Unauthorized)]
+    pub mxe_config: Account<'info, MxeConfig>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + CommitmentTree::INIT_SPACE,
+        seeds = [COMMITMENT_TREE_SEED],
+        bump
+    )]
+    pub commitment_tree: Account<'info, CommitmentTree>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct ComputationCallback<'info> {
+    #[account(mut, seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MxeConfig>,
+    #[account(
+        mut,
+        seeds = [COMPUTATION_SEED, &computation_offset.to_le_bytes()],
+        bump = computation.bump
+    )]
+    pub computation: Account<'info, Computation>,
+    /// CHECK: address is constrained to the instructions sysvar
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct FinalizeComputation<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [MXE_CONFIG_SEED],
+        bump = mxe_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub mxe_config: Account<'info, MxeConfig>,
+    #[account(
+        mut,
+        seeds = [COMPUTATION_SEED, &computation_offset.to_le_bytes()],
+        bump = computation.bump
+    )]
+    pub computation: Account<'info, Computation>,
+}
+
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct EscalateComputation<'info> {
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MxeConfig>,
+    #[account(
+        mut,
+        seeds = [COMPUTATION_SEED, &computation_offset.to_le_bytes()],
+        bump = computation.bump
+    )]
+    pub computation: Account<'info, Computation>,
+}
+
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct AuditComputation<'info> {
+    #[account(
+        seeds = [COMPUTATION_SEED, &computation_offset.to_le_bytes()],
+        bump = computation.bump
+    )]
+    pub computation: Account<'info, Computation>,
+}
+
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct ReclaimComputation<'info> {
+    pub requester: Signer<'info>,
+    #[account(mut, seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MxeConfig>,
+    #[account(
+        mut,
+        seeds = [COMPUTATION_SEED, &computation_offset.to_le_bytes()],
+        bump = computation.bump,
+        has_one = requester
+    )]
+    pub computation: Account<'info, Computation>,
+}
+
+// State
+#[account]
+#[derive(InitSpace)]
+pub struct MxeConfig {
+    pub authority: Pubkey,
+    pub max_pending_computations: u64,
+    pub pending_computations: u64,
+    pub min_ciphertext_bytes: u16,
+    pub max_ciphertext_bytes: u16,
+    pub max_slippage_bps: u16,
+    pub clock_skew_secs: u32,
+    pub require_monotonic_offsets: bool,
+    pub allow_testnet_btc: bool,
+    pub paused: bool,
+    pub protocol_fee_bps: u16,
+    pub soft_timeout_secs: u32,
+    pub hard_timeout_secs: u32,
+    pub max_price_staleness_secs: u32,
+    pub treasury: Pubkey,
+    pub computation_fee: u64,
+    pub arcium_program: Pubkey,
+    #[max_len(MAX_ALLOWED_CHAINS, MAX_CHAIN_NAME_LEN)]
+    pub allowed_chains: Vec<String>,
+    #[max_len(MAX_OPERATORS)]
+    pub operators: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl MxeConfig {
+    fn accepts_ciphertext(&self, bytes: &[u8]) -> bool {
+        (self.min_ciphertext_bytes as usize..=self.max_ciphertext_bytes as usize)
+            .contains(&bytes.len())
+    }
+
+    fn is_chain_allowed(&self, chain: &str) -> bool {
+        self.allowed_chains.is_empty() || self.allowed_chains.iter().any(|allowed| allowed == chain)
+    }
+
+    fn is_operator(&self, key: &Pubkey) -> bool {
+        self.operators.contains(key)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct MxeConfigParams {
+    pub max_pending_computations: u64,
+    pub min_ciphertext_bytes: u16,
+    pub max_ciphertext_bytes: u16,
+    pub max_slippage_bps: u16,
+    pub clock_skew_secs: u32,
+    pub protocol_fee_bps: u16,
+    pub soft_timeout_secs: u32,
+    pub hard_timeout_secs: u32,
+    pub max_price_staleness_secs: u32,
+    pub require_monotonic_offsets: bool,
+    pub allow_testnet_btc: bool,
+    pub treasury: Pubkey,
+    pub computation_fee: u64,
+    pub arcium_program: Pubkey,
+    pub allowed_chains: Vec<String>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Computation {
+    pub requester: Pubkey,
+    pub computation_offset: u64,
+    pub status: ComputationStatus,
+    pub kind: ComputationKind,
+    pub primary_commitment: [u8; 32],
+    pub secondary_commitment: [u8; 32],
+    pub created_at: i64,
+    pub soft_timeout_at: i64,
+    pub priority: u8,
+    pub finalized_at: i64,
+    pub result_commitment: [u8; 32],
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RequesterState {
+    pub requester: Pubkey,
+    pub last_offset: u64,
+    pub computations_queued: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ChainFee {
+    #[max_len(MAX_CHAIN_NAME_LEN)]
+    pub source_chain: String,
+    #[max_len(MAX_CHAIN_NAME_LEN)]
+    pub dest_chain: String,
+    pub fee: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PriceFeed {
+    pub rate: u64,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+/// Append-only Merkle tree over bridge commitments. Only the rightmost path
+/// (`frontier`) is stored; empty subtrees hash from zero leaves.
+#[account]
+#[derive(InitSpace)]
+pub struct CommitmentTree {
+    pub root: [u8; 32],
+    pub next_index: u64,
+    pub frontier: [[u8; 32]; COMMITMENT_TREE_DEPTH],
+    pub bump: u8,
+}
+
+impl CommitmentTree {
+    fn append(&mut self, leaf: [u8; 32]) -> Result<u64> {
+        let leaf_index = self.next_index;
+        require!(
+            leaf_index < 1u64 << COMMITMENT_TREE_DEPTH,
+            ErrorCode::CommitmentTreeFull
+        );
+
+        let mut node = hash_merkle_leaf(&leaf);
+        let mut zero = [0u8; 32];
+        let mut index = leaf_index;
+        for sibling in self.frontier.iter_mut() {
+            node = if index & 1 == 0 {
+                *sibling = node;
+                hash_merkle_pair(&node, &zero)
+            } else {
+                hash_merkle_pair(sibling, &node)
+            };
+            zero = hash_merkle_pair(&zero, &zero);
+            index >>= 1;
+        }
+
+        self.root = node;
+        self.next_index = leaf_index + 1;
+        Ok(leaf_index)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct BtcAddressEncryption {
+    pub recipient: Pubkey,
+    pub btc_address_commitment: [u8; 32],
+    pub computation_offset: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ComputationStatus {
+    Queued,
+    Finalized,
+    Reclaimed,
+}
+
+// The circuit a computation was queued for; callbacks only finalize their own kind
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ComputationKind {
+    EncryptBridgeAmount,
+    VerifyBridgeTransaction,
+    CalculateSwapAmount,
+    CalculateMultihopSwap,
+    CompareAmounts,
+    NetBridgeBatch,
+    CheckReportable,
+    VerifyBtcAddressAllowed,
+    EncryptBtcAddress,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SwapRoute {
+    pub hops: Vec<SwapHop>,
+}
+
+/// One leg of a route, priced by the `base`/`quote` pair feed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SwapHop {
+    pub base: String,
+    pub quote: String,
+    pub slippage_bps: u16,
 }
 
 // Events
@@ -258,15 +2309,35 @@ pub struct BridgeAmountEncryptionQueued {
     pub source_chain: String,
     pub dest_chain: String,
     pub amount_commitment: [u8; 32],
+    pub nonce_commitment: [u8; 32],
+    pub chain_fee: u64,
+    pub expected_slippage_bps: u16,
+    pub estimated_min_out: u64,
+    pub leaf_index: u64,
+    pub commitment_root: [u8; 32],
+    pub computation_fee: u64,
     pub computation_offset: u64,
     pub timestamp: i64,
 }
 
+#[event]
+pub struct BridgeAmountOpened {
+    pub computation_offset: u64,
+    pub requester: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct BridgeVerificationQueued {
     pub tx_hash_commitment: [u8; 32],
     pub blockchain: String,
     pub expected_amount_commitment: [u8; 32],
+    pub deadline: i64,
+    pub leaf_index: u64,
+    pub commitment_root: [u8; 32],
+    pub computation_fee: u64,
     pub computation_offset: u64,
     pub timestamp: i64,
 }
@@ -275,8 +2346,64 @@ pub struct BridgeVerificationQueued {
 pub struct SwapCalculationQueued {
     pub zen_amount_commitment: [u8; 32],
     pub exchange_rate: u64,
-    pub slippage_tolerance: u64,
+    pub price_updated_at: i64,
+    pub slippage_bps: u16,
+    pub protocol_fee_bps: u16,
     pub sol_amount: u64,
+    pub fee_breakdown_commitment: [u8; 32],
+    pub deadline: i64,
+    pub computation_fee: u64,
+    pub computation_offset: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MultihopSwapCalculationQueued {
+    pub zen_amount_commitment: [u8; 32],
+    pub hop_rates: Vec<u64>,
+    pub price_updated_at: i64,
+    pub hops: Vec<SwapHop>,
+    pub hop_amounts: Vec<u64>,
+    pub protocol_fee_bps: u16,
+    pub sol_amount: u64,
+    pub deadline: i64,
+    pub computation_fee: u64,
+    pub computation_offset: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ComparisonQueued {
+    pub a_commitment: [u8; 32],
+    pub b_commitment: [u8; 32],
+    pub computation_fee: u64,
+    pub computation_offset: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BatchNettingQueued {
+    pub amount_commitments: Vec<[u8; 32]>,
+    pub batch_commitment: [u8; 32],
+    pub computation_fee: u64,
+    pub computation_offset: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReportableCheckQueued {
+    pub amount_commitment: [u8; 32],
+    pub threshold: u64,
+    pub computation_fee: u64,
+    pub computation_offset: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AddressAllowlistCheckQueued {
+    pub address_commitment: [u8; 32],
+    pub allowlist_root: [u8; 32],
+    pub computation_fee: u64,
     pub computation_offset: u64,
     pub timestamp: i64,
 }
@@ -285,7 +2412,64 @@ pub struct SwapCalculationQueued {
 pub struct BtcAddressEncryptionQueued {
     pub recipient: Pubkey,
     pub btc_address_commitment: [u8; 32],
+    pub computation_fee: u64,
+    pub computation_offset: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BridgeAmountEncryptionCompleted {
     pub computation_offset: u64,
+    pub requester: Pubkey,
+    pub output: Vec<u8>,
+    pub result_commitment: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SwapCalculationCompleted {
+    pub computation_offset: u64,
+    pub requester: Pubkey,
+    pub output: Vec<u8>,
+    pub result_commitment: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ComputationFinalized {
+    pub computation_offset: u64,
+    pub requester: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CommitmentBatchVerified {
+    pub root: [u8; 32],
+    pub verified_count: u32,
+    pub total: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ComputationEscalated {
+    pub computation_offset: u64,
+    pub requester: Pubkey,
+    pub priority: u8,
+    pub next_soft_timeout_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuditBundle {
+    pub computation_offset: u64,
+    pub requester: Pubkey,
+    pub kind: ComputationKind,
+    pub status: ComputationStatus,
+    pub primary_commitment: [u8; 32],
+    pub secondary_commitment: [u8; 32],
+    pub result_commitment: [u8; 32],
+    pub created_at: i64,
+    pub finalized_at: i64,
     pub timestamp: i64,
 }
 
@@ -307,4 +2491,446 @@ pub enum ErrorCode {
     InvalidSwapInputs,
     #[msg("Arithmetic overflow")]
     Overflow,
+    #[msg("Too many pending computations")]
+    QueueFull,
+    #[msg("Pending computation limit must be greater than zero")]
+    InvalidPendingLimit,
+    #[msg("Computation is not queued")]
+    ComputationNotQueued,
+    #[msg("Invalid swap route")]
+    InvalidSwapRoute,
+    #[msg("Deadline has passed")]
+    DeadlineExpired,
+    #[msg("Clock skew tolerance exceeds the maximum")]
+    InvalidClockSkew,
+    #[msg("Computation offset must exceed the requester's last offset")]
+    NonMonotonicOffset,
+    #[msg("Protocol fee exceeds the maximum")]
+    InvalidProtocolFee,
+    #[msg("Invalid commitment batch")]
+    InvalidCommitmentBatch,
+    #[msg("Merkle proof does not match the root")]
+    InvalidMerkleProof,
+    #[msg("Soft timeout must be greater than zero and below the hard timeout")]
+    InvalidSoftTimeout,
+    #[msg("Computation has not reached its soft timeout")]
+    SoftTimeoutNotReached,
+    #[msg("Invalid ciphertext bounds")]
+    InvalidCiphertextBounds,
+    #[msg("Too many allowed chains")]
+    TooManyAllowedChains,
+    #[msg("Chain is not in the allowlist")]
+    ChainNotAllowed,
+    #[msg("Signer is not the MXE authority")]
+    Unauthorized,
+    #[msg("Slippage exceeds 100%")]
+    InvalidSlippage,
+    #[msg("Invalid netting batch size")]
+    InvalidNettingBatch,
+    #[msg("Payer cannot cover the computation fee")]
+    InsufficientComputationFee,
+    #[msg("Allowlist root cannot be empty")]
+    InvalidAllowlistRoot,
+    #[msg("Opening does not match the stored commitment")]
+    CommitmentMismatch,
+    #[msg("Callback was not invoked by the Arcium program")]
+    UnauthorizedCallback,
+    #[msg("Computation offset is already in use")]
+    DuplicateComputationOffset,
+    #[msg("Unsupported chain")]
+    UnsupportedChain,
+    #[msg("Price feed is stale")]
+    StalePrice,
+    #[msg("Invalid price staleness window")]
+    InvalidPriceStaleness,
+    #[msg("Signer is not an allowlisted operator")]
+    UnauthorizedOperator,
+    #[msg("Too many operators")]
+    TooManyOperators,
+    #[msg("Commitment tree is full")]
+    CommitmentTreeFull,
+    #[msg("Nonce cannot be all zeroes")]
+    InvalidNonce,
+    #[msg("Callback does not match the computation's kind")]
+    ComputationKindMismatch,
+    #[msg("Computation fee would leave the treasury below the rent-exempt minimum")]
+    TreasuryNotRentExempt,
+    #[msg("Invalid asset pair")]
+    InvalidAssetPair,
+    #[msg("Price feed does not match the swap hop")]
+    InvalidPriceFeed,
+    #[msg("Hard timeout must exceed the soft timeout")]
+    InvalidHardTimeout,
+    #[msg("Computation has not reached its hard timeout")]
+    HardTimeoutNotReached,
+}
+
+#[cfg(test)]
+mod commitment_vectors {
+    //! Canonical keccak commitment vectors for SDKs reproducing the program's
+    //! hashing conventions byte-for-byte.
+
+    use super::*;
+
+    const KNOWN_USER: [u8; 32] = [7u8; 32];
+    const BTC_ADDRESS: &str = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+
+    const EMPTY_COMMITMENT: &str =
+        "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470";
+    const TAG_COMMITMENT: &str = "2de4e9a0ef417ac1d5484b7809cb3cd00122a68cc6b300840fa758d2444e287e";
+    const BRIDGE_AMOUNT_COMMITMENT: &str =
+        "1a3d381142f876c7ccdd10e7862d54a4252859e51c56673f138dc5876865bba1";
+    const MAX_CHAIN_BRIDGE_AMOUNT_COMMITMENT: &str =
+        "136b72ddf2636a5d8b8b778cd5696854e8e195b1d715eac2aef068ad353af2b1";
+    const FEE_BREAKDOWN_COMMITMENT: &str =
+        "51bc71b5e2ed9c78a42807bf5f2d62ef6f1843c49f1d9e433e4fd76f2b7911cf";
+    const KNOWN_OFFSET: u64 = 42;
+    const KNOWN_NONCE: [u8; 32] = [9u8; 32];
+    const DERIVED_SALT: &str = "ed7aefdfa696b0b8b945e9501db0201b";
+    const BTC_ADDRESS_ENCRYPTION_KEY: &str =
+        "639a986af84ed5ab0f2754715059074ac72cc5845e76ef816945b6112e23bbd5";
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    #[test]
+    fn commitment_of_empty_input() {
+        assert_eq!(to_hex(&commitment(b"")), EMPTY_COMMITMENT);
+    }
+
+    #[test]
+    fn commitment_of_tag() {
+        assert_eq!(to_hex(&commitment(b"FLASH_BRIDGE_V1")), TAG_COMMITMENT);
+    }
+
+    #[test]
+    fn bridge_amount_commitment_for_known_user() {
+        let user = Pubkey::new_from_array(KNOWN_USER);
+        assert_eq!(
+            to_hex(&commit_bridge_amount(
+                1_000_000,
+                0,
+                "ZEC",
+                "SOL",
+                &user,
+                &derive_salt(&user, KNOWN_OFFSET),
+                &KNOWN_NONCE
+            )),
+            BRIDGE_AMOUNT_COMMITMENT
+        );
+    }
+
+    #[test]
+    fn bridge_amount_commitment_with_max_length_chains() {
+        let source_chain = "A".repeat(MAX_CHAIN_NAME_LEN);
+        let dest_chain = "B".repeat(MAX_CHAIN_NAME_LEN);
+        assert_eq!(
+            to_hex(&commit_bridge_amount(
+                u64::MAX,
+                1_250,
+                &source_chain,
+                &dest_chain,
+                &ID,
+                &derive_salt(&ID, u64::MAX),
+                &[u8::MAX; 32]
+            )),
+            MAX_CHAIN_BRIDGE_AMOUNT_COMMITMENT
+        );
+    }
+
+    #[test]
+    fn derived_salt_for_known_user() {
+        let user = Pubkey::new_from_array(KNOWN_USER);
+        assert_eq!(to_hex(&derive_salt(&user, KNOWN_OFFSET)), DERIVED_SALT);
+        assert_ne!(
+            derive_salt(&user, KNOWN_OFFSET),
+            derive_salt(&user, KNOWN_OFFSET + 1)
+        );
+    }
+
+    #[test]
+    fn bridge_amount_commitment_reopens_from_offset_requester_and_nonce() {
+        let requester = Pubkey::new_from_array(KNOWN_USER);
+        let committed = commit_bridge_amount(
+            1_000_000,
+            0,
+            "ZEC",
+            "SOL",
+            &requester,
+            &derive_salt(&requester, KNOWN_OFFSET),
+            &KNOWN_NONCE,
+        );
+        let reopen = |offset, nonce: &[u8; 32]| {
+            commit_bridge_amount(
+                1_000_000,
+                0,
+                "ZEC",
+                "SOL",
+                &requester,
+                &derive_salt(&requester, offset),
+                nonce,
+            )
+        };
+        assert_eq!(reopen(KNOWN_OFFSET, &KNOWN_NONCE), committed);
+        assert_ne!(reopen(KNOWN_OFFSET + 1, &KNOWN_NONCE), committed);
+        assert_ne!(reopen(KNOWN_OFFSET, &[10u8; 32]), committed);
+    }
+
+    #[test]
+    fn chain_aliases_normalize_to_canonical_names() {
+        for (input, expected) in [
+            ("btc", "BTC"),
+            (" Bitcoin ", "BTC"),
+            ("ZCASH", "ZEC"),
+            ("solana", "SOL"),
+            ("Ethereum", "ETH"),
+        ] {
+            assert_eq!(normalize_chain(input.to_string()).unwrap(), expected);
+            assert_eq!(chain_seed(input), expected.as_bytes());
+        }
+        assert_eq!(
+            normalize_chain("DOGE".to_string()).unwrap_err(),
+            error!(ErrorCode::UnsupportedChain)
+        );
+        let oversized = "X".repeat(64);
+        assert_eq!(chain_seed(&oversized), UNSUPPORTED_CHAIN_SEED);
+        assert_eq!(
+            normalize_chain(oversized).unwrap_err(),
+            error!(ErrorCode::UnsupportedChain)
+        );
+        assert!(normalize_chain("   ".to_string()).is_err());
+    }
+
+    #[test]
+    fn swap_routes_must_chain_zen_to_sol() {
+        let hop = |base: &str, quote: &str| SwapHop {
+            base: base.to_string(),
+            quote: quote.to_string(),
+            slippage_bps: 0,
+        };
+        assert!(is_connected_route(&[hop("ZEN", "SOL")]));
+        assert!(is_connected_route(&[
+            hop("ZEN", "USDC"),
+            hop("USDC", "SOL")
+        ]));
+        assert!(!is_connected_route(&[]));
+        assert!(!is_connected_route(&[
+            hop("ZEN", "USDC"),
+            hop("USDT", "SOL")
+        ]));
+        assert!(!is_connected_route(&[hop("USDC", "SOL")]));
+        assert!(!is_connected_route(&[hop("zen", "sol")]));
+
+        let oversized = "X".repeat(64);
+        assert_eq!(asset_seed(&oversized), UNSUPPORTED_ASSET_SEED);
+        assert_eq!(asset_seed("USDC"), b"USDC");
+    }
+
+    #[test]
+    fn btc_address_validation_accepts_known_mainnet_addresses() {
+        for address in [
+            "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa",
+            "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy",
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+            "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+            "bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3",
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+            BTC_ADDRESS,
+        ] {
+            assert!(is_valid_btc_address(address, false), "{address}");
+        }
+    }
+
+    #[test]
+    fn btc_address_validation_rejects_bad_checksums_and_hrps() {
+        for address in [
+            // corrupted checksums
+            "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb",
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5",
+            // bech32 checksum on a taproot (bech32m) address
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd",
+            // wrong HRP and mixed case
+            "ltc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+            "bc1qW508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+            "zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz",
+        ] {
+            assert!(!is_valid_btc_address(address, false), "{address}");
+        }
+    }
+
+    #[test]
+    fn btc_address_validation_length_boundaries() {
+        // Shortest P2PKH encoding and the longest witness program (40 bytes)
+        let shortest = "11111111111111111111BZbvjr";
+        let longest = "bc1pqqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc0jqgfzyvjz2f38wjxkpz";
+        assert_eq!(shortest.len(), MIN_BTC_ADDRESS_LEN);
+        assert!(is_valid_btc_address(shortest, false));
+        assert_eq!(longest.len(), 74);
+        assert!(is_valid_btc_address(longest, false));
+
+        // Correctly checksummed bech32m strings past the program limit and past the length cap
+        let oversized_program = format!("bc1p{}dx8cyj", "q".repeat(80));
+        let over_length = format!("bc1p{}y7geld", "q".repeat(81));
+        assert_eq!(oversized_program.len(), MAX_BTC_ADDRESS_LEN);
+        assert!(!is_valid_btc_address(&oversized_program, false));
+        assert_eq!(over_length.len(), MAX_BTC_ADDRESS_LEN + 1);
+        assert!(!is_valid_btc_address(&over_length, false));
+    }
+
+    #[test]
+    fn btc_address_validation_rejects_non_zero_padding() {
+        // 32-byte v1 program with the lowest of its 4 padding bits set, checksum recomputed
+        assert!(!is_valid_btc_address(
+            "bc1pqqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc034zlwws",
+            false
+        ));
+    }
+
+    #[test]
+    fn btc_address_validation_gates_testnet_prefixes() {
+        for address in [
+            "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx",
+            "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+        ] {
+            assert!(!is_valid_btc_address(address, false), "{address}");
+            assert!(is_valid_btc_address(address, true), "{address}");
+        }
+    }
+
+    #[test]
+    fn check_deadline_allows_skew_then_reports_deadline_expired() {
+        assert!(check_deadline(100, 100, 0).is_ok());
+        assert!(check_deadline(130, 100, 30).is_ok());
+        assert_eq!(
+            check_deadline(131, 100, 30).unwrap_err(),
+            error!(ErrorCode::DeadlineExpired)
+        );
+    }
+
+    #[test]
+    fn fee_breakdown_commitment() {
+        assert_eq!(
+            to_hex(&commit_fee_breakdown(200_000, 59_400, 19_740_600)),
+            FEE_BREAKDOWN_COMMITMENT
+        );
+    }
+
+    #[test]
+    fn btc_address_encryption_key_for_known_user() {
+        let user = Pubkey::new_from_array(KNOWN_USER);
+        assert_eq!(
+            to_hex(&btc_address_encryption_key(&user, BTC_ADDRESS)),
+            BTC_ADDRESS_ENCRYPTION_KEY
+        );
+        assert_eq!(
+            btc_address_encryption_key(&user, &format!("  {BTC_ADDRESS}\n")),
+            btc_address_encryption_key(&user, BTC_ADDRESS)
+        );
+        assert_eq!(
+            btc_address_encryption_key(&user, &BTC_ADDRESS.to_ascii_uppercase()),
+            btc_address_encryption_key(&user, BTC_ADDRESS)
+        );
+    }
+
+    #[test]
+    fn btc_address_encryption_key_keeps_base58_case() {
+        let user = Pubkey::new_from_array(KNOWN_USER);
+        let address = "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2";
+        assert_ne!(
+            btc_address_encryption_key(&user, &address.to_ascii_uppercase()),
+            btc_address_encryption_key(&user, address)
+        );
+    }
+
+    fn new_commitment_tree() -> CommitmentTree {
+        CommitmentTree {
+            root: empty_commitment_tree_root(),
+            next_index: 0,
+            frontier: [[0u8; 32]; COMMITMENT_TREE_DEPTH],
+            bump: 0,
+        }
+    }
+
+    /// Builds every level of a depth-20 tree over `leaves`, padding each
+    /// level with the empty-subtree hash for that height.
+    fn full_tree_levels(leaves: &[[u8; 32]]) -> Vec<Vec<[u8; 32]>> {
+        let mut levels = vec![leaves.iter().map(hash_merkle_leaf).collect::<Vec<_>>()];
+        let mut zero = [0u8; 32];
+        for _ in 0..COMMITMENT_TREE_DEPTH {
+            let mut level = levels.last().unwrap().clone();
+            if level.len() % 2 == 1 {
+                level.push(zero);
+            }
+            let parents = level
+                .chunks(2)
+                .map(|pair| hash_merkle_pair(&pair[0], &pair[1]))
+                .collect();
+            *levels.last_mut().unwrap() = level;
+            levels.push(parents);
+            zero = hash_merkle_pair(&zero, &zero);
+        }
+        levels
+    }
+
+    #[test]
+    fn commitment_tree_roots_match_full_tree() {
+        let leaves: Vec<[u8; 32]> = (0u8..7).map(|i| commitment(&[i])).collect();
+        let mut tree = new_commitment_tree();
+
+        for (i, leaf) in leaves.iter().enumerate() {
+            assert_eq!(tree.append(*leaf).unwrap(), i as u64);
+            let levels = full_tree_levels(&leaves[..=i]);
+            assert_eq!(tree.root, levels[COMMITMENT_TREE_DEPTH][0]);
+        }
+        assert_eq!(tree.next_index, leaves.len() as u64);
+    }
+
+    #[test]
+    fn commitment_tree_paths_recompute_root() {
+        let leaves: Vec<[u8; 32]> = (0u8..5).map(|i| commitment(&[i])).collect();
+        let mut tree = new_commitment_tree();
+        for leaf in &leaves {
+            tree.append(*leaf).unwrap();
+        }
+
+        let levels = full_tree_levels(&leaves);
+        for (i, leaf) in leaves.iter().enumerate() {
+            let path: Vec<[u8; 32]> = (0..COMMITMENT_TREE_DEPTH)
+                .map(|height| levels[height][(i >> height) ^ 1])
+                .collect();
+            assert_eq!(merkle_root_from_proof(*leaf, &path), tree.root);
+        }
+    }
+
+    #[test]
+    fn commitment_tree_rejects_appends_once_full() {
+        let mut tree = new_commitment_tree();
+        tree.next_index = 1u64 << COMMITMENT_TREE_DEPTH;
+        assert!(tree.append([1u8; 32]).is_err());
+    }
+
+    #[test]
+    fn inclusion_rejects_short_paths_and_interior_nodes() {
+        let leaves: Vec<[u8; 32]> = (0u8..4).map(|i| commitment(&[i])).collect();
+        let mut tree = new_commitment_tree();
+        for leaf in &leaves {
+            tree.append(*leaf).unwrap();
+        }
+        let levels = full_tree_levels(&leaves);
+        // Leaf 0 sits leftmost, so its sibling is always at index 1
+        let path: Vec<[u8; 32]> = (0..COMMITMENT_TREE_DEPTH)
+            .map(|height| levels[height][1])
+            .collect();
+
+        assert!(is_committed(leaves[0], &path, &tree.root));
+        // The root with an empty path
+        assert!(!is_committed(tree.root, &[], &tree.root));
+        // An interior node with the remainder of a valid path
+        assert!(!is_committed(levels[1][0], &path[1..], &tree.root));
+        // The same interior node padded to full depth still hashes as a leaf
+        let mut padded = path[1..].to_vec();
+        padded.push([0u8; 32]);
+        assert!(!is_committed(levels[1][0], &padded, &tree.root));
+    }
 }